@@ -0,0 +1,177 @@
+use crate::{
+    anytime::Anytime,
+    bidirectional::Bidirectional,
+    d_star_lite::DStarLite,
+    deepening::Deepening,
+    fog::Explorer,
+    hierarchical::Hierarchical,
+    jump_point::JumpPoint,
+    landmarks::Landmarks,
+    map::{Edit, Map},
+    multi_agent::ConflictBased,
+    search::{Algorithm, Strategy},
+    space_time::SpaceTime,
+    waypoints::WaypointRoute,
+};
+use std::time::Duration;
+
+// Knobs for the algorithms that take them
+pub struct Settings {
+    pub epsilon: f64,      // heuristic weight for weighted A*, and where ARA* starts
+    pub epsilon_step: f64, // how much ARA* lowers epsilon each pass
+    pub max_expansions: Option<usize>,
+    pub time_limit: Option<Duration>,
+    pub edits: Vec<Edit>, // changes D* Lite makes to the map as it goes
+    pub landmarks: usize, // how many landmarks A* with ALT uses
+    pub landmark_file: Option<String>, // where the map's landmark table is kept, if anywhere
+    pub cluster: usize,   // how many spots across HPA*'s clusters are
+    pub compare: bool,    // have HPA* time plain A* on the same query too
+    pub sensor_radius: usize, // how far LRTA*'s agent can see
+    pub fog_cost: usize,  // what LRTA* assumes spots it hasn't seen cost
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            epsilon: 2.0,
+            epsilon_step: 0.5,
+            max_expansions: None,
+            time_limit: None,
+            edits: vec![],
+            landmarks: 8,
+            landmark_file: None,
+            cluster: 16,
+            compare: true,
+            sensor_radius: 2,
+            fog_cost: 1,
+        }
+    }
+}
+
+// Every algorithm's name, in the order `all` runs them
+pub const NAMES: &[&str] = &[
+    "breadth_first",
+    "lowest_cost",
+    "greedy_best_first",
+    "a_star_1",
+    "a_star_2",
+    "a_star_3",
+    "a_star_4",
+    "a_star_alt",
+    "iddfs",
+    "ida_star_1",
+    "ida_star_2",
+    "ida_star_3",
+    "ida_star_4",
+    "bidirectional_bfs",
+    "bidirectional_a_star_1",
+    "bidirectional_a_star_2",
+    "bidirectional_a_star_3",
+    "bidirectional_a_star_4",
+    "jump_point",
+    "weighted_a_star",
+    "ara_star",
+    "d_star_lite",
+    "waypoints",
+    "cbs",
+    "space_time_a_star",
+    "hpa_star",
+    "lrta_star",
+];
+
+pub fn from_name(name: &str, map: &Map, settings: &Settings) -> Option<Box<dyn Algorithm>> {
+    // without diagonal moves octile is the same as taxicab
+    let diagonal = map.diagonals.unwrap_or(2.0);
+    let alg: Box<dyn Algorithm> = match name {
+        "breadth_first" | "bfs" => Box::new(Strategy::breadth_first()),
+        "lowest_cost" | "lowest_cost_path" => Box::new(Strategy::lowest_cost_path()),
+        "greedy_best_first" | "greedy" => Box::new(Strategy::greedy_best_first()),
+        "a_star_1" | "a_star_taxicab" => Box::new(Strategy::a_star_taxicab()),
+        "a_star_2" | "a_star_euclidean" => Box::new(Strategy::a_star_euclidean()),
+        "a_star_3" | "a_star_octile" => Box::new(Strategy::a_star_octile(diagonal)),
+        "a_star_4" | "a_star_hex" => Box::new(Strategy::a_star_hex()),
+        "a_star_alt" | "a_star_landmarks" => Box::new(Strategy::a_star_landmarks(
+            Landmarks::for_map(map, settings.landmarks, settings.landmark_file.as_deref()),
+        )),
+        "iddfs" | "iterative_deepening" => Box::new(Deepening::iterative_deepening()),
+        "ida_star_1" | "ida_star_taxicab" => Box::new(Deepening::ida_star_taxicab()),
+        "ida_star_2" | "ida_star_euclidean" => Box::new(Deepening::ida_star_euclidean()),
+        "ida_star_3" | "ida_star_octile" => Box::new(Deepening::ida_star_octile(diagonal)),
+        "ida_star_4" | "ida_star_hex" => Box::new(Deepening::ida_star_hex()),
+        "bidirectional_bfs" => Box::new(Bidirectional::breadth_first()),
+        "bidirectional_a_star_1" | "bidirectional_a_star_taxicab" => {
+            Box::new(Bidirectional::a_star_taxicab())
+        }
+        "bidirectional_a_star_2" | "bidirectional_a_star_euclidean" => {
+            Box::new(Bidirectional::a_star_euclidean())
+        }
+        "bidirectional_a_star_3" | "bidirectional_a_star_octile" => {
+            Box::new(Bidirectional::a_star_octile(diagonal))
+        }
+        "bidirectional_a_star_4" | "bidirectional_a_star_hex" => {
+            Box::new(Bidirectional::a_star_hex())
+        }
+        "jump_point" | "jps" => Box::new(JumpPoint::new(map.dist_mode())),
+        "weighted_a_star" => Box::new(Anytime::weighted_a_star(map.dist_mode(), settings.epsilon)),
+        "ara_star" => Box::new(Anytime::ara_star(
+            map.dist_mode(),
+            settings.epsilon,
+            settings.epsilon_step,
+            settings.max_expansions,
+            settings.time_limit,
+        )),
+        "d_star_lite" | "dstar_lite" => Box::new(DStarLite::new(settings.edits.clone())),
+        "waypoints" | "waypoint_route" => Box::new(WaypointRoute::new()),
+        "cbs" | "conflict_based" => Box::new(ConflictBased::new()),
+        "space_time_a_star" | "space_time" => Box::new(SpaceTime::new()),
+        "hpa_star" | "hierarchical" => {
+            let mut hpa = Hierarchical::new(map, settings.cluster);
+            hpa.compare = settings.compare;
+            Box::new(hpa)
+        }
+        "lrta_star" | "fog" => Box::new(Explorer::new(settings.sensor_radius, settings.fog_cost)),
+        _ => return None,
+    };
+    Some(alg)
+}
+
+// Octile variants, which only differ from taxicab when diagonal moves are allowed, and
+// the ones that overestimate when they are: a diagonal step covers two spots of taxicab
+// distance, and a road diagonal can cost less than its euclidean length
+const DIAGONAL_ONLY: &[&str] = &["a_star_3", "ida_star_3", "bidirectional_a_star_3"];
+const STRAIGHT_ONLY: &[&str] = &[
+    "a_star_1",
+    "a_star_2",
+    "ida_star_1",
+    "ida_star_2",
+    "bidirectional_a_star_1",
+    "bidirectional_a_star_2",
+];
+
+// Hex variants, and the ones that don't work on hex maps: taxicab and euclidean
+// distances overestimate there, and jump point search is just hex A*
+const HEX_ONLY: &[&str] = &["a_star_4", "ida_star_4", "bidirectional_a_star_4"];
+const SQUARE_ONLY: &[&str] = &[
+    "a_star_1",
+    "a_star_2",
+    "ida_star_1",
+    "ida_star_2",
+    "bidirectional_a_star_1",
+    "bidirectional_a_star_2",
+    "jump_point",
+];
+
+// Every algorithm that makes sense for the map
+pub fn all(map: &Map, settings: &Settings) -> Vec<Box<dyn Algorithm>> {
+    let other_grid = if map.hex { SQUARE_ONLY } else { HEX_ONLY };
+    let other_moves = match map.diagonals {
+        Some(_) => STRAIGHT_ONLY,
+        None => DIAGONAL_ONLY,
+    };
+    NAMES
+        .iter()
+        .filter(|name| !other_moves.contains(name))
+        .filter(|name| !other_grid.contains(name))
+        .filter_map(|name| from_name(name, map, settings))
+        .collect()
+}
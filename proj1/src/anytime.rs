@@ -0,0 +1,262 @@
+use crate::{
+    map::{DistMode, Map, Status, Vec2},
+    search::{report, Algorithm, Counts, Heuristic, Outcome},
+    trace::Event,
+    Output,
+};
+use priority_queue::PriorityQueue;
+use std::{
+    cmp::Reverse,
+    time::{Duration, Instant},
+};
+
+// Anytime repairing A* (ARA*): A* with the heuristic inflated by epsilon, which finds
+// a path fast that costs at most epsilon times the best one. It then lowers epsilon
+// and repairs the search it already did instead of starting over, publishing every
+// better path it finds, until epsilon reaches 1 (the path is optimal) or it runs out
+// of budget. Weighted A* is just the first of those passes.
+pub struct Anytime {
+    pub name: String,
+    pub title: String,
+    pub label: String,
+    pub failure: String,
+    pub heuristic: Heuristic,
+    pub epsilon: f64,      // how much the heuristic is inflated to start with
+    pub epsilon_step: f64, // how much epsilon drops each pass, or 0 for only one pass
+    pub max_expansions: Option<usize>,
+    pub time_limit: Option<Duration>,
+}
+
+// Priority queue key for a score, lowest first. Scores are never negative, and the bits
+// of non-negative floats sort the same way the floats do.
+fn key(f: f64) -> Reverse<u64> {
+    Reverse(f.to_bits())
+}
+
+impl Anytime {
+    pub fn weighted_a_star(mode: DistMode, epsilon: f64) -> Self {
+        Self {
+            name: "weighted_a_star".into(),
+            title: format!("weighted A* search (epsilon: {epsilon})"),
+            label: "weighted A*".into(),
+            failure: "Weighted A* search failed!".into(),
+            heuristic: Heuristic::Dist(mode),
+            epsilon,
+            epsilon_step: 0.0,
+            max_expansions: None,
+            time_limit: None,
+        }
+    }
+
+    pub fn ara_star(
+        mode: DistMode,
+        epsilon: f64,
+        epsilon_step: f64,
+        max_expansions: Option<usize>,
+        time_limit: Option<Duration>,
+    ) -> Self {
+        Self {
+            name: "ara_star".into(),
+            title: format!("ARA* search (epsilon: {epsilon}, step: {epsilon_step})"),
+            label: "ARA*".into(),
+            failure: "ARA* search failed!".into(),
+            heuristic: Heuristic::Dist(mode),
+            epsilon,
+            epsilon_step,
+            max_expansions,
+            time_limit,
+        }
+    }
+}
+
+impl Algorithm for Anytime {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn run(&self, map: &Map, out: &mut Output) -> Outcome {
+        // Variables
+        let mut view = map.clone();
+        let (start, goal) = (map.start, map.goal);
+        let size = map.dim.0 * map.dim.1;
+        let h = |loc: Vec2| self.heuristic.estimate(loc, goal) as f64;
+        let f = |g: usize, loc: Vec2, epsilon: f64| g as f64 + epsilon * h(loc);
+        let mut g = vec![usize::MAX; size];
+        let mut from: Vec<Option<Vec2>> = vec![None; size];
+        let mut closed = vec![false; size];
+        let mut open = PriorityQueue::new();
+        let mut incons: Vec<Vec2> = vec![]; // improved after being expanded this pass
+        let mut best: Option<(usize, Vec<Vec2>)> = None; // (cost, path) published so far
+        let mut epsilon = self.epsilon.max(1.0);
+        let mut out_of_budget = false;
+        let clock = Instant::now();
+        let mut counts = Counts::default();
+
+        // Initialization
+        view.costs = vec![usize::MAX; map.dim.0 * map.dim.1];
+        view.display_costs = true;
+        view.at_mut(start).unwrap().1 = Status::Path;
+        g[map.index(start)] = map.at(start).unwrap().0.cost();
+        view.costs[map.index(start)] = g[map.index(start)];
+        open.push(start, key(0.0));
+        counts.pushed(open.len());
+        out.event(Event::Push {
+            loc: start,
+            from: None,
+            g: g[map.index(start)],
+            priority: 0.0,
+        });
+
+        // Loop
+        out.text(&format!("Running {}\n", self.title));
+        'passes: loop {
+            // Improve the path until nothing left in the queue could beat it
+            let pops_prev = counts.pops;
+            while let Some((&loc, &Reverse(top))) = open.peek() {
+                let g_goal = g[map.index(goal)];
+                if g_goal != usize::MAX && f(g_goal, goal, epsilon) <= f64::from_bits(top) {
+                    break;
+                }
+                if self.max_expansions.is_some_and(|max| counts.pops >= max)
+                    || self
+                        .time_limit
+                        .is_some_and(|limit| clock.elapsed() >= limit)
+                {
+                    // the bound only holds for a finished pass, so stick with the last path
+                    out.text("Ran out of budget\n");
+                    out_of_budget = true;
+                    break 'passes;
+                }
+                open.pop();
+                counts.pops += 1;
+                out.event(Event::Pop {
+                    loc,
+                    g: g[map.index(loc)],
+                    priority: f64::from_bits(top),
+                });
+                out.frame(&view);
+                view.at_mut(loc).unwrap().1.deactivate();
+                out.event(Event::Deactivate { loc });
+                closed[map.index(loc)] = true;
+
+                let cost = g[map.index(loc)];
+                for loc_new in map.moves(loc) {
+                    let i = map.index(loc_new);
+                    let maybe_cost = cost + map.step_cost(loc, loc_new);
+                    if maybe_cost < g[i] {
+                        let priority = f(maybe_cost, loc_new, epsilon);
+                        out.event(Event::reached(g[i], loc_new, loc, maybe_cost, priority));
+                        g[i] = maybe_cost;
+                        from[i] = Some(loc);
+                        // spots that were already expanded wait for the next pass
+                        if closed[i] {
+                            incons.push(loc_new);
+                        } else {
+                            open.push(loc_new, key(priority));
+                            counts.pushed(open.len());
+                        }
+                        view.at_mut(loc_new).unwrap().1 = map.toward(loc_new, loc, true);
+                        view.costs[map.index(loc_new)] = maybe_cost;
+                    }
+                }
+            }
+            // How far from optimal the path could be: nothing still queued can reach the
+            // goal for less than its cost so far plus its (uninflated) heuristic
+            let g_goal = g[map.index(goal)];
+            let lower = open
+                .iter()
+                .map(|(&loc, _)| loc)
+                .chain(incons.iter().copied())
+                .map(|loc| g[map.index(loc)] as f64 + h(loc))
+                .fold(f64::INFINITY, f64::min);
+            out.text(&format!(
+                "Searched with epsilon {epsilon} ({} expanded)\n",
+                counts.pops - pops_prev
+            ));
+
+            // Publish the path if it's better than the last one. Spots can get cheaper
+            // after the spots past them were expanded, so the path the back-pointers
+            // give can cost less than the goal's g, and is costed again here.
+            let mut bound = epsilon;
+            if g_goal != usize::MAX {
+                let mut path = vec![goal];
+                while let Some(prev) = from[map.index(*path.last().unwrap())] {
+                    path.push(prev);
+                }
+                path.reverse();
+                let cost = map.at(start).unwrap().0.cost()
+                    + path
+                        .windows(2)
+                        .map(|pair| map.step_cost(pair[0], pair[1]))
+                        .sum::<usize>();
+                bound = (cost as f64 / lower).clamp(1.0, epsilon);
+                if best.as_ref().is_none_or(|(best_cost, _)| cost < *best_cost) {
+                    out.event(Event::Goal { loc: goal, g: cost });
+                    let mut shown = view.clone();
+                    shown.trail(&path);
+                    out.frame(&shown);
+                    out.text(&format!(
+                        "Improved path (dist: {} cost: {cost}) is at most {bound:.3} times optimal\n",
+                        path.len()
+                    ));
+                    best = Some((cost, path));
+                }
+            }
+
+            if bound <= 1.0 {
+                if best.is_some() {
+                    out.text("The path is optimal\n");
+                }
+                break;
+            }
+            if self.epsilon_step <= 0.0 || open.is_empty() && incons.is_empty() {
+                break;
+            }
+
+            // Lower epsilon, and requeue everything with the new scores for another pass
+            epsilon = (epsilon - self.epsilon_step).max(1.0);
+            let queued: Vec<Vec2> = open
+                .into_iter()
+                .map(|(loc, _)| loc)
+                .chain(incons.drain(..))
+                .collect();
+            open = PriorityQueue::new();
+            for loc in queued {
+                open.push(loc, key(f(g[map.index(loc)], loc, epsilon)));
+            }
+            closed.fill(false);
+        }
+
+        // Show the best path found
+        for (loc, _) in open {
+            view.at_mut(loc).unwrap().1.deactivate();
+            out.event(Event::Deactivate { loc });
+        }
+        if let Some((_, path)) = &best {
+            view.trail(path);
+            out.frame(&view);
+        } else if out_of_budget {
+            // there may well be a path, it just wasn't found in time
+            out.event(Event::Fail);
+            out.text(&format!(
+                "{} Ran out of budget before any path was found\n",
+                self.failure
+            ));
+            out.flush();
+            return Outcome {
+                path: None,
+                route: vec![],
+                pops: counts.pops,
+                generated: counts.generated,
+                max_frontier: counts.max_frontier,
+            };
+        }
+        report(
+            best.is_some().then_some(&mut view),
+            &self.label,
+            &self.failure,
+            counts,
+            out,
+        )
+    }
+}
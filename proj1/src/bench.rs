@@ -0,0 +1,125 @@
+use crate::{map::Map, search::Algorithm, Output};
+use std::time::{Duration, Instant};
+
+// One algorithm's run on one map, for comparing algorithms without reading the results
+// files
+pub struct Row {
+    pub map: String,
+    pub algorithm: String,
+    pub path: Option<(usize, usize)>, // (dist, cost)
+    pub pops: usize,
+    pub generated: usize,
+    pub max_frontier: usize,
+    pub time: Duration,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Csv,
+    Json,
+}
+
+const COLUMNS: [&str; 8] = [
+    "map",
+    "algorithm",
+    "path_length",
+    "path_cost",
+    "pops",
+    "generated",
+    "max_frontier",
+    "time_ms",
+];
+
+// Run an algorithm on a map without writing anything, timing it
+pub fn measure(name: &str, map: &Map, alg: &dyn Algorithm) -> Row {
+    let clock = Instant::now();
+    let outcome = alg.run(map, &mut Output::silent());
+    Row {
+        map: name.into(),
+        algorithm: alg.name().into(),
+        path: outcome.path,
+        pops: outcome.pops,
+        generated: outcome.generated,
+        max_frontier: outcome.max_frontier,
+        time: clock.elapsed(),
+    }
+}
+
+impl Row {
+    // The columns as text, with the path length and cost left empty if there's no path
+    fn values(&self) -> [String; 8] {
+        let (dist, cost) = match self.path {
+            Some((dist, cost)) => (dist.to_string(), cost.to_string()),
+            None => (String::new(), String::new()),
+        };
+        [
+            self.map.clone(),
+            self.algorithm.clone(),
+            dist,
+            cost,
+            self.pops.to_string(),
+            self.generated.to_string(),
+            self.max_frontier.to_string(),
+            format!("{:.3}", self.time.as_secs_f64() * 1000.0),
+        ]
+    }
+}
+
+pub fn header(format: Format) -> String {
+    match format {
+        Format::Csv => format!("{}\n", COLUMNS.join(",")),
+        Format::Json => "[\n".into(),
+    }
+}
+
+// A row as a line of CSV, or as a JSON object (`first` says whether it needs a comma
+// before it)
+pub fn line(row: &Row, format: Format, first: bool) -> String {
+    let values = row.values();
+    match format {
+        Format::Csv => {
+            let fields: Vec<String> = values
+                .iter()
+                .map(|v| match v.contains([',', '"', '\n']) {
+                    true => format!("\"{}\"", v.replace('"', "\"\"")),
+                    false => v.clone(),
+                })
+                .collect();
+            format!("{}\n", fields.join(","))
+        }
+        Format::Json => {
+            let fields: Vec<String> = COLUMNS
+                .iter()
+                .zip(values)
+                .enumerate()
+                .map(|(i, (column, v))| match i {
+                    0 | 1 => format!("\"{column}\": \"{}\"", escape(&v)),
+                    _ if v.is_empty() => format!("\"{column}\": null"),
+                    _ => format!("\"{column}\": {v}"),
+                })
+                .collect();
+            let comma = if first { "" } else { ",\n" };
+            format!("{comma}  {{{}}}", fields.join(", "))
+        }
+    }
+}
+
+pub fn footer(format: Format) -> String {
+    match format {
+        Format::Csv => String::new(),
+        Format::Json => "\n]\n".into(),
+    }
+}
+
+fn escape(s: &str) -> String {
+    let mut escaped = String::new();
+    for c in s.chars() {
+        match c {
+            '"' => escaped += "\\\"",
+            '\\' => escaped += "\\\\",
+            c if (c as u32) < 0x20 => escaped += &format!("\\u{:04x}", c as u32),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
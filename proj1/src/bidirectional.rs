@@ -0,0 +1,212 @@
+use crate::{
+    map::{DistMode, Map, Status, Vec2},
+    search::{report, Algorithm, Counts, Heuristic, Outcome},
+    trace::Event,
+    Output,
+};
+use priority_queue::PriorityQueue;
+use std::cmp::Reverse;
+
+// Searches out from the start and the goal at the same time, until the two meet in
+// the middle. The forward half leaves back-pointers toward the start and the backward
+// half toward the goal; once they meet, the best meeting spot stitches them together.
+pub struct Bidirectional {
+    pub name: String,
+    pub title: String,
+    pub label: String,
+    pub failure: String,
+    pub heuristic: Heuristic,
+    pub use_cost: bool, // search by path cost (A*) instead of the number of steps (BFS)
+}
+
+const FORWARD: usize = 0;
+const BACKWARD: usize = 1;
+
+impl Bidirectional {
+    pub fn breadth_first() -> Self {
+        Self {
+            name: "bidirectional_bfs".into(),
+            title: "bidirectional breadth first search".into(),
+            label: "bidirectional breadth first".into(),
+            failure: "Bidirectional breadth first search failed!".into(),
+            heuristic: Heuristic::None,
+            use_cost: false,
+        }
+    }
+
+    pub fn a_star_taxicab() -> Self {
+        Self::a_star(DistMode::TaxiCab, "1", "taxicab", "taxicab")
+    }
+
+    pub fn a_star_euclidean() -> Self {
+        Self::a_star(DistMode::Euclidean, "2", "euclidean", "euclid")
+    }
+
+    pub fn a_star_octile(diagonal: f64) -> Self {
+        Self::a_star(DistMode::Octile(diagonal), "3", "octile", "octile")
+    }
+
+    pub fn a_star_hex() -> Self {
+        Self::a_star(DistMode::Hex, "4", "hex", "hex")
+    }
+
+    fn a_star(mode: DistMode, n: &str, dist_name: &str, short: &str) -> Self {
+        Self {
+            name: format!("bidirectional_a_star_{n}"),
+            title: format!("bidirectional A* search (heuristic: {dist_name} dist)"),
+            label: format!("bidirectional A* ({short})"),
+            failure: "Bidirectional A* search failed!".into(),
+            heuristic: Heuristic::Dist(mode),
+            use_cost: true,
+        }
+    }
+
+    // The cost of the move from `loc` to `loc_new` as seen by one side; the backward
+    // side walks the real moves in reverse
+    fn step(&self, map: &Map, side: usize, loc: Vec2, loc_new: Vec2) -> usize {
+        match (self.use_cost, side) {
+            (false, _) => 1,
+            (true, FORWARD) => map.step_cost(loc, loc_new),
+            (true, _) => map.step_cost(loc_new, loc),
+        }
+    }
+}
+
+impl Algorithm for Bidirectional {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn run(&self, map: &Map, out: &mut Output) -> Outcome {
+        // Variables
+        let mut view = map.clone();
+        let (start, goal) = (map.start, map.goal);
+        let targets = [goal, start];
+        let size = map.dim.0 * map.dim.1;
+        let mut g = [vec![usize::MAX; size], vec![usize::MAX; size]];
+        let mut from: [Vec<Option<Vec2>>; 2] = [vec![None; size], vec![None; size]];
+        let mut q = [PriorityQueue::new(), PriorityQueue::new()];
+        let mut best: Option<(usize, Vec2)> = None; // cheapest (cost, meeting spot) so far
+        let mut side = FORWARD;
+        let mut counts = Counts::default();
+
+        // Initialization
+        view.costs = vec![usize::MAX; map.dim.0 * map.dim.1];
+        view.display_costs = self.use_cost;
+        let start_cost = if self.use_cost {
+            map.at(start).unwrap().0.cost()
+        } else {
+            0
+        };
+        for (s, (loc, cost)) in [(start, start_cost), (goal, 0)].into_iter().enumerate() {
+            g[s][map.index(loc)] = cost;
+            let f = cost.saturating_add(self.heuristic.estimate(loc, targets[s]));
+            q[s].push(loc, Reverse(f));
+            counts.pushed(q[0].len() + q[1].len());
+            out.event(Event::Push {
+                loc,
+                from: None,
+                g: cost,
+                priority: f as f64,
+            });
+            view.at_mut(loc).unwrap().1 = Status::Path;
+            view.costs[map.index(loc)] = cost;
+        }
+        if start == goal {
+            best = Some((start_cost, start));
+        }
+
+        // Loop
+        out.text(&format!("Running {}\n", self.title));
+        loop {
+            // Stop once neither side can find anything cheaper than the best meeting
+            let top = |s: usize| q[s].peek().map_or(usize::MAX, |(_, &Reverse(f))| f);
+            if let Some((mu, _)) = best {
+                // without a heuristic the two sides' costs can also be added together
+                let sum = match self.heuristic {
+                    Heuristic::None => top(FORWARD).saturating_add(top(BACKWARD)),
+                    _ => 0,
+                };
+                if top(FORWARD) >= mu || top(BACKWARD) >= mu || sum >= mu {
+                    break;
+                }
+            }
+            if q[side].is_empty() {
+                // one side has run dry, so the other has to finish on its own
+                side = 1 - side;
+            }
+            let Some((loc, Reverse(f))) = q[side].pop() else {
+                break;
+            };
+            counts.pops += 1;
+            out.event(Event::Pop {
+                loc,
+                g: g[side][map.index(loc)],
+                priority: f as f64,
+            });
+            out.frame(&view);
+            view.at_mut(loc).unwrap().1.deactivate();
+            out.event(Event::Deactivate { loc });
+
+            // Relax every neighbor, and check whether the other side has reached it too
+            let cost = g[side][map.index(loc)];
+            for loc_new in map.moves(loc) {
+                let i = map.index(loc_new);
+                let maybe_cost = cost + self.step(map, side, loc, loc_new);
+                if maybe_cost < g[side][i] {
+                    let f =
+                        maybe_cost.saturating_add(self.heuristic.estimate(loc_new, targets[side]));
+                    out.event(Event::reached(
+                        g[side][i], loc_new, loc, maybe_cost, f as f64,
+                    ));
+                    g[side][i] = maybe_cost;
+                    from[side][i] = Some(loc);
+                    q[side].push(loc_new, Reverse(f));
+                    counts.pushed(q[0].len() + q[1].len());
+                    if g[1 - side][i] == usize::MAX {
+                        view.at_mut(loc_new).unwrap().1 = map.toward(loc_new, loc, true);
+                        view.costs[map.index(loc_new)] = maybe_cost;
+                    }
+                }
+                let other = g[1 - side][i];
+                if other != usize::MAX {
+                    let total = g[side][i] + other;
+                    if best.is_none_or(|(mu, _)| total < mu) {
+                        out.event(Event::Goal {
+                            loc: loc_new,
+                            g: total,
+                        });
+                        best = Some((total, loc_new));
+                    }
+                }
+            }
+
+            side = 1 - side;
+        }
+
+        // Stitch the two halves together at the meeting spot
+        for (loc, _) in q.into_iter().flatten() {
+            view.at_mut(loc).unwrap().1.deactivate();
+            out.event(Event::Deactivate { loc });
+        }
+        if let Some((_, meet)) = best {
+            let mut path = vec![meet];
+            while let Some(prev) = from[FORWARD][map.index(*path.last().unwrap())] {
+                path.push(prev);
+            }
+            path.reverse();
+            while let Some(next) = from[BACKWARD][map.index(*path.last().unwrap())] {
+                path.push(next);
+            }
+            view.trail(&path);
+            out.frame(&view);
+        }
+        report(
+            best.is_some().then_some(&mut view),
+            &self.label,
+            &self.failure,
+            counts,
+            out,
+        )
+    }
+}
@@ -0,0 +1,306 @@
+use crate::{
+    map::{dist, DistMode, Edit, Map, Terrain, Vec2},
+    search::{report, Algorithm, Counts, Outcome},
+    trace::Event,
+    Output,
+};
+use priority_queue::PriorityQueue;
+use std::cmp::Reverse;
+
+// D* Lite: searches backward from the goal, so that when the map changes under an agent
+// that's already on its way, only the part of the search the change touched has to be
+// redone. `g` is the cost to the goal the last time a spot was expanded and `rhs` is
+// what it would be now from its neighbors' `g`s. The spots where they disagree are
+// queued, and planning fixes them up until the agent's spot is settled.
+pub struct Planner {
+    pub map: Map,
+    pub position: Vec2, // where the agent is now
+    mode: DistMode,
+    g: Vec<usize>,
+    rhs: Vec<usize>,
+    queue: PriorityQueue<Vec2, Reverse<(usize, usize)>>,
+    km: usize,          // how far the agent has moved since planning started, added to keys
+    last: Vec2,         // the agent's spot when km was last brought up to date
+    pub counts: Counts, // all the planning done so far
+}
+
+impl Planner {
+    pub fn new(map: &Map, out: &mut Output) -> Self {
+        let size = map.dim.0 * map.dim.1;
+        let mut planner = Self {
+            map: map.clone(),
+            position: map.start,
+            mode: map.dist_mode(),
+            g: vec![usize::MAX; size],
+            rhs: vec![usize::MAX; size],
+            queue: PriorityQueue::new(),
+            km: 0,
+            last: map.start,
+            counts: Counts::default(),
+        };
+        let goal = map.goal;
+        planner.rhs[map.index(goal)] = 0;
+        let key = planner.key(goal);
+        planner.queue.push(goal, key);
+        planner.counts.pushed(planner.queue.len());
+        out.event(Event::Push {
+            loc: goal,
+            from: None,
+            g: 0,
+            priority: key.0 .0 as f64,
+        });
+        planner
+    }
+
+    fn key(&self, loc: Vec2) -> Reverse<(usize, usize)> {
+        let i = self.map.index(loc);
+        let best = self.g[i].min(self.rhs[i]);
+        let h = dist(self.position, loc, self.mode);
+        Reverse((best.saturating_add(h).saturating_add(self.km), best))
+    }
+
+    // Recompute a spot's rhs from its neighbors, and (re)queue it if it's out of date.
+    // Its events say which neighbor the rhs came through, since the search runs backward.
+    fn update(&mut self, loc: Vec2, out: &mut Output) {
+        let i = self.map.index(loc);
+        let mut from = None;
+        if loc != self.map.goal {
+            let best = match self.map.at(loc) {
+                Some(_) => self
+                    .map
+                    .moves(loc)
+                    .into_iter()
+                    .map(|next| {
+                        let g = self.g[self.map.index(next)];
+                        (g.saturating_add(self.map.step_cost(loc, next)), next)
+                    })
+                    .min(),
+                None => None,
+            };
+            self.rhs[i] = best.map_or(usize::MAX, |(rhs, _)| rhs);
+            from = best
+                .filter(|&(rhs, _)| rhs != usize::MAX)
+                .map(|(_, next)| next);
+        }
+        if self.g[i] != self.rhs[i] {
+            let key = self.key(loc);
+            let (g, priority) = (self.rhs[i], key.0 .0 as f64);
+            match (self.queue.push(loc, key), from) {
+                (None, _) => {
+                    self.counts.pushed(self.queue.len());
+                    out.event(Event::Push {
+                        loc,
+                        from,
+                        g,
+                        priority,
+                    });
+                }
+                (Some(_), Some(from)) => out.event(Event::Relax {
+                    loc,
+                    from,
+                    g,
+                    priority,
+                }),
+                (Some(_), None) => (),
+            }
+        } else if self.queue.remove(&loc).is_some() {
+            out.event(Event::Deactivate { loc });
+        }
+    }
+
+    // Bring the search up to date, returning how many spots it expanded
+    pub fn plan(&mut self, out: &mut Output) -> usize {
+        let mut pops = 0;
+        loop {
+            let here = self.map.index(self.position);
+            let Some((&loc, &old_key)) = self.queue.peek() else {
+                break;
+            };
+            if old_key <= self.key(self.position) && self.rhs[here] == self.g[here] {
+                break;
+            }
+            pops += 1;
+            self.counts.pops += 1;
+            let i = self.map.index(loc);
+            out.event(Event::Pop {
+                loc,
+                g: self.g[i].min(self.rhs[i]),
+                priority: old_key.0 .0 as f64,
+            });
+            let new_key = self.key(loc);
+            if old_key > new_key {
+                // the key went stale while the agent moved
+                self.queue.push(loc, new_key);
+            } else if self.g[i] > self.rhs[i] {
+                self.g[i] = self.rhs[i];
+                self.queue.remove(&loc);
+                out.event(Event::Deactivate { loc });
+                for prev in self.map.moves(loc) {
+                    self.update(prev, out);
+                }
+            } else {
+                self.g[i] = usize::MAX;
+                self.update(loc, out);
+                for prev in self.map.moves(loc) {
+                    self.update(prev, out);
+                }
+            }
+        }
+        pops
+    }
+
+    // Change the terrain of a spot, and queue up everything whose moves it affects
+    pub fn edit(&mut self, loc: Vec2, terrain: Terrain, out: &mut Output) {
+        let Some(spot) = self.map.at_mut(loc) else {
+            return;
+        };
+        spot.0 = terrain;
+        self.km += dist(self.last, self.position, self.mode);
+        self.last = self.position;
+        // a spot's neighbors (diagonal ones too, for corner cutting) have moves onto it
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                if let (Some(x), Some(y)) =
+                    (loc.0.checked_add_signed(dx), loc.1.checked_add_signed(dy))
+                {
+                    if x < self.map.dim.0 && y < self.map.dim.1 {
+                        self.update((x, y), out);
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn advance(&mut self, loc: Vec2) {
+        self.position = loc;
+    }
+
+    // The cost of the best path from the agent's spot (counting the spot itself, like
+    // the other searches do), if there is one
+    pub fn cost(&self) -> Option<usize> {
+        let g = self.g[self.map.index(self.position)];
+        (g != usize::MAX)
+            .then(|| g.saturating_add(self.map.map[self.map.index(self.position)].0.cost()))
+    }
+
+    // The best path from the agent's spot to the goal, if there is one
+    pub fn path(&self) -> Option<Vec<Vec2>> {
+        self.cost()?;
+        let mut path = vec![self.position];
+        let mut loc = self.position;
+        while loc != self.map.goal {
+            loc = self.map.moves(loc).into_iter().min_by_key(|&next| {
+                self.g[self.map.index(next)].saturating_add(self.map.step_cost(loc, next))
+            })?;
+            if path.len() > self.g.len() {
+                return None;
+            }
+            path.push(loc);
+        }
+        Some(path)
+    }
+}
+
+// Walks an agent from the start to the goal with D* Lite, changing the map along the way
+pub struct DStarLite {
+    pub name: String,
+    pub title: String,
+    pub label: String,
+    pub failure: String,
+    pub edits: Vec<Edit>,
+}
+
+impl DStarLite {
+    pub fn new(edits: Vec<Edit>) -> Self {
+        Self {
+            name: "d_star_lite".into(),
+            title: format!("D* Lite search ({} map edits)", edits.len()),
+            label: "D* Lite".into(),
+            failure: "D* Lite search failed!".into(),
+            edits,
+        }
+    }
+}
+
+// A walk with any loops cut out of it
+fn without_loops(walk: &[Vec2]) -> Vec<Vec2> {
+    let mut path: Vec<Vec2> = vec![];
+    for &loc in walk {
+        if let Some(i) = path.iter().position(|&l| l == loc) {
+            path.truncate(i);
+        }
+        path.push(loc);
+    }
+    path
+}
+
+impl Algorithm for DStarLite {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn run(&self, map: &Map, out: &mut Output) -> Outcome {
+        // Variables
+        let mut planner = Planner::new(map, out);
+        let mut walk = vec![map.start];
+        let mut cost = map.at(map.start).unwrap().0.cost();
+        let mut step = 0;
+        let mut done = false;
+
+        // Loop
+        out.text(&format!("Running {}\n", self.title));
+        loop {
+            // Make this step's changes to the map, then repair the plan
+            let edits: Vec<&Edit> = self.edits.iter().filter(|e| e.step == step).collect();
+            for edit in &edits {
+                out.text(&format!(
+                    "Step {step}: set cell {},{} to terrain {}\n",
+                    edit.loc.0,
+                    edit.loc.1,
+                    map.terrains.name(edit.terrain)
+                ));
+                planner.edit(edit.loc, edit.terrain, out);
+            }
+            if step == 0 || !edits.is_empty() {
+                let expanded = planner.plan(out);
+                out.text(&format!("Planned at step {step} ({expanded} expanded)\n"));
+            }
+            if planner.position == map.goal {
+                out.event(Event::Goal {
+                    loc: map.goal,
+                    g: cost,
+                });
+                done = true;
+                break;
+            }
+            let Some(path) = planner.path() else {
+                break;
+            };
+
+            // Show where the agent has been and where it's headed, then take a step
+            let mut view = planner.map.clone();
+            let mut route = without_loops(&walk);
+            route.extend(&path[1..]);
+            view.trail(&route);
+            out.frame(&view);
+            cost += planner.map.step_cost(path[0], path[1]);
+            planner.advance(path[1]);
+            walk.push(path[1]);
+            step += 1;
+        }
+
+        let mut view = planner.map.clone();
+        if done {
+            out.text(&format!("Walked {step} steps for a total cost of {cost}\n"));
+            view.trail(&without_loops(&walk));
+            out.frame(&view);
+        }
+        report(
+            done.then_some(&mut view),
+            &self.label,
+            &self.failure,
+            planner.counts,
+            out,
+        )
+    }
+}
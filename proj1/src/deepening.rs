@@ -0,0 +1,227 @@
+use crate::{
+    map::{DistMode, Map, Vec2},
+    search::{report, Algorithm, Counts, Heuristic, Outcome},
+    trace::Event,
+    Output,
+};
+
+// Iterative deepening searches: repeated depth first searches out to a growing bound.
+// They only keep the current path and one number per spot in memory, instead of
+// a whole frontier and a copy of the map.
+pub struct Deepening {
+    pub name: String,
+    pub title: String,
+    pub label: String,
+    pub failure: String,
+    pub heuristic: Heuristic,
+    pub use_cost: bool, // bound the path cost (IDA*) instead of the number of steps
+}
+
+// A spot on the depth first stack, with the moves out of it that are left to try
+struct Frame {
+    loc: Vec2,
+    g: usize,    // what the bound is on: the cost or the number of steps so far
+    cost: usize, // the cost so far, counting the start's terrain
+    moves: Vec<Vec2>,
+}
+
+impl Deepening {
+    pub fn iterative_deepening() -> Self {
+        Self {
+            name: "iddfs".into(),
+            title: "iterative deepening depth first search".into(),
+            label: "iterative deepening".into(),
+            failure: "Iterative deepening search failed!".into(),
+            heuristic: Heuristic::None,
+            use_cost: false,
+        }
+    }
+
+    pub fn ida_star_taxicab() -> Self {
+        Self {
+            name: "ida_star_1".into(),
+            title: "IDA* search (heuristic: taxicab dist)".into(),
+            label: "IDA* (taxicab)".into(),
+            failure: "IDA* search failed!".into(),
+            heuristic: Heuristic::Dist(DistMode::TaxiCab),
+            use_cost: true,
+        }
+    }
+
+    pub fn ida_star_euclidean() -> Self {
+        Self {
+            name: "ida_star_2".into(),
+            title: "IDA* search (heuristic: euclidean dist)".into(),
+            label: "IDA* (euclid)".into(),
+            failure: "IDA* search failed!".into(),
+            heuristic: Heuristic::Dist(DistMode::Euclidean),
+            use_cost: true,
+        }
+    }
+
+    pub fn ida_star_octile(diagonal: f64) -> Self {
+        Self {
+            name: "ida_star_3".into(),
+            title: "IDA* search (heuristic: octile dist)".into(),
+            label: "IDA* (octile)".into(),
+            failure: "IDA* search failed!".into(),
+            heuristic: Heuristic::Dist(DistMode::Octile(diagonal)),
+            use_cost: true,
+        }
+    }
+
+    pub fn ida_star_hex() -> Self {
+        Self {
+            name: "ida_star_4".into(),
+            title: "IDA* search (heuristic: hex dist)".into(),
+            label: "IDA* (hex)".into(),
+            failure: "IDA* search failed!".into(),
+            heuristic: Heuristic::Dist(DistMode::Hex),
+            use_cost: true,
+        }
+    }
+
+    fn step(&self, map: &Map, from: Vec2, to: Vec2) -> usize {
+        if self.use_cost {
+            map.step_cost(from, to)
+        } else {
+            1
+        }
+    }
+
+    fn moves(map: &Map, loc: Vec2) -> Vec<Vec2> {
+        // reversed so that popping tries them in the usual order
+        let mut moves = map.moves(loc);
+        moves.reverse();
+        moves
+    }
+
+    // Depth first search out to `bound`. Returns the path (start first) if the goal
+    // was reached, or else the smallest score that went over the bound.
+    // `best` holds the cheapest way to each spot found so far in this pass, so spots
+    // that were already reached at least as cheaply aren't searched again. The deepest
+    // the stack gets stands in for the frontier size.
+    fn probe(
+        &self,
+        map: &Map,
+        bound: usize,
+        best: &mut [usize],
+        counts: &mut Counts,
+        out: &mut Output,
+    ) -> Result<Vec<Vec2>, usize> {
+        let (start, goal) = (map.start, map.goal);
+        let cost = map.at(start).unwrap().0.cost();
+        let priority = self.heuristic.estimate(start, goal) as f64;
+        out.event(Event::Push {
+            loc: start,
+            from: None,
+            g: cost,
+            priority,
+        });
+        if start == goal {
+            out.event(Event::Goal { loc: goal, g: cost });
+            return Ok(vec![start]);
+        }
+        let mut next_bound = usize::MAX;
+        best.fill(usize::MAX);
+        best[map.index(start)] = 0;
+        let mut stack = vec![Frame {
+            loc: start,
+            g: 0,
+            cost,
+            moves: Self::moves(map, start),
+        }];
+        counts.pops += 1;
+        counts.pushed(stack.len());
+        out.event(Event::Pop {
+            loc: start,
+            g: cost,
+            priority,
+        });
+
+        while let Some(frame) = stack.last_mut() {
+            let Some(loc_new) = frame.moves.pop() else {
+                stack.pop();
+                continue;
+            };
+            let g = frame.g + self.step(map, frame.loc, loc_new);
+            let cost = frame.cost + map.step_cost(frame.loc, loc_new);
+            let i = map.index(loc_new);
+            if g >= best[i] {
+                continue;
+            }
+            let f = g.saturating_add(self.heuristic.estimate(loc_new, goal));
+            out.event(Event::reached(best[i], loc_new, frame.loc, cost, f as f64));
+            best[i] = g;
+            counts.generated += 1;
+
+            if f > bound {
+                next_bound = next_bound.min(f);
+                continue;
+            }
+            if loc_new == goal {
+                out.event(Event::Goal { loc: goal, g: cost });
+                let mut path: Vec<Vec2> = stack.iter().map(|frame| frame.loc).collect();
+                path.push(goal);
+                return Ok(path);
+            }
+            counts.pops += 1;
+            out.event(Event::Pop {
+                loc: loc_new,
+                g: cost,
+                priority: f as f64,
+            });
+            stack.push(Frame {
+                loc: loc_new,
+                g,
+                cost,
+                moves: Self::moves(map, loc_new),
+            });
+            counts.max_frontier = counts.max_frontier.max(stack.len());
+        }
+        Err(next_bound)
+    }
+}
+
+impl Algorithm for Deepening {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn run(&self, map: &Map, out: &mut Output) -> Outcome {
+        // Variables
+        let mut best = vec![usize::MAX; map.dim.0 * map.dim.1];
+        let mut bound = self.heuristic.estimate(map.start, map.goal);
+        let mut counts = Counts::default();
+        let bound_name = if self.use_cost { "cost" } else { "depth" };
+
+        // Loop
+        out.text(&format!("Running {}\n", self.title));
+        let found = loop {
+            let pops_prev = counts.pops;
+            let probe = self.probe(map, bound, &mut best, &mut counts, out);
+            out.text(&format!(
+                "Searched to {bound_name} {bound} ({} expanded)\n",
+                counts.pops - pops_prev
+            ));
+            match probe {
+                Ok(path) => break Some(path),
+                Err(usize::MAX) => break None,
+                Err(next) => bound = next,
+            }
+        };
+
+        let mut map = map.clone();
+        if let Some(path) = &found {
+            map.trail(path);
+            out.frame(&map);
+        }
+        report(
+            found.is_some().then_some(&mut map),
+            &self.label,
+            &self.failure,
+            counts,
+            out,
+        )
+    }
+}
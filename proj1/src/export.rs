@@ -0,0 +1,343 @@
+use crate::map::{Map, Status, Terrain, Vec2};
+use std::time::Duration;
+
+// How a spot is drawn in one frame of a search
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shade {
+    Unseen,
+    Frontier,
+    Visited,
+    Path,
+    Hazard,
+}
+
+impl Shade {
+    pub fn of(status: Status) -> Self {
+        match status {
+            Status::Untraversed => Shade::Unseen,
+            Status::Path | Status::Agent(_) => Shade::Path,
+            Status::Hazard => Shade::Hazard,
+            s if s.is_active() => Shade::Frontier,
+            _ => Shade::Visited,
+        }
+    }
+
+    // The letter the HTML player keeps frames as
+    fn code(self) -> char {
+        match self {
+            Shade::Unseen => 'u',
+            Shade::Frontier => 'f',
+            Shade::Visited => 'v',
+            Shade::Path => 'p',
+            Shade::Hazard => 'h',
+        }
+    }
+
+    fn color(self) -> &'static str {
+        match self {
+            Shade::Unseen => "none",
+            Shade::Frontier => "#ffd740",
+            Shade::Visited => "#37474f",
+            Shade::Path => "#e53935",
+            Shade::Hazard => "#8e24aa",
+        }
+    }
+}
+
+// Colors for the standard terrain, and shades of brown (darker costs more) for any other
+pub fn terrain_color(terrain: Terrain) -> String {
+    let color = match terrain.symbol() {
+        'R' => "#d7c49e",
+        'f' => "#aed581",
+        'F' => "#388e3c",
+        'h' => "#a1887f",
+        'r' => "#4fc3f7",
+        'M' => "#757575",
+        'W' => "#1e5aa8",
+        '?' => "#9e9e9e", // not seen yet, in fog of war
+        _ if !terrain.passable() => "#263238",
+        _ => {
+            let dark = (terrain.cost().min(20) * 8) as u8;
+            return format!("#{:02x}{:02x}{:02x}", 230 - dark, 210 - dark, 170 - dark);
+        }
+    };
+    color.into()
+}
+
+struct Frame {
+    shades: Vec<Shade>,
+    edits: Vec<(usize, Terrain)>, // spots whose terrain changed since the frame before
+}
+
+// Every frame a search drew, kept so it can be played back as a picture
+#[derive(Default)]
+pub struct Recording {
+    dim: Vec2,
+    hex: bool,             // odd rows are drawn half a spot to the right
+    terrain: Vec<Terrain>, // as of the first frame
+    latest: Vec<Terrain>,
+    labels: Vec<(Vec2, String)>, // the start, goal and waypoints
+    legend: Vec<(Terrain, String)>,
+    frames: Vec<Frame>,
+}
+
+const CELL: usize = 20; // pixels per spot
+
+impl Recording {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub fn capture(&mut self, map: &Map) {
+        let terrain: Vec<Terrain> = map.map.iter().map(|spot| spot.0).collect();
+        if self.frames.is_empty() {
+            self.dim = map.dim;
+            self.hex = map.hex;
+            self.terrain = terrain.clone();
+            self.latest = terrain.clone();
+            self.labels = vec![(map.start, "S".into()), (map.goal, "G".into())];
+            for (i, &w) in map.waypoints.iter().enumerate() {
+                self.labels.push((w, (i + 1).to_string()));
+            }
+        }
+        let edits: Vec<(usize, Terrain)> = (0..terrain.len())
+            .filter(|&i| terrain[i] != self.latest[i])
+            .map(|i| (i, terrain[i]))
+            .collect();
+        let new = match self.frames.is_empty() {
+            true => &terrain[..],
+            false => &edits.iter().map(|&(_, t)| t).collect::<Vec<_>>(),
+        };
+        for &t in new {
+            if !self.legend.iter().any(|&(known, _)| known == t) {
+                self.legend.push((t, map.terrains.name(t).into()));
+            }
+        }
+        self.latest = terrain;
+        self.frames.push(Frame {
+            shades: map.map.iter().map(|spot| Shade::of(spot.1)).collect(),
+            edits,
+        });
+    }
+
+    fn xy(&self, i: usize) -> (usize, usize) {
+        let (x, y) = (i % self.dim.0, i / self.dim.0);
+        let shift = if self.hex && y % 2 == 1 { CELL / 2 } else { 0 };
+        (x * CELL + shift, y * CELL)
+    }
+
+    // The terrain and the labels, with an empty marker on every spot that gets one.
+    // Markers are a smaller square in the middle, so the terrain shows around them.
+    fn grid(&self, animate: impl Fn(usize, bool) -> String) -> String {
+        let mut s = String::new();
+        for (i, &t) in self.terrain.iter().enumerate() {
+            let (x, y) = self.xy(i);
+            s += &format!(
+                "<rect id=\"t{i}\" x=\"{x}\" y=\"{y}\" width=\"{CELL}\" height=\"{CELL}\" fill=\"{}\">{}</rect>\n",
+                terrain_color(t),
+                animate(i, true)
+            );
+        }
+        let (inset, size) = (CELL / 4, CELL / 2);
+        for i in 0..self.terrain.len() {
+            let (x, y) = self.xy(i);
+            s += &format!(
+                "<rect id=\"m{i}\" x=\"{}\" y=\"{}\" width=\"{size}\" height=\"{size}\" fill=\"none\">{}</rect>\n",
+                x + inset,
+                y + inset,
+                animate(i, false)
+            );
+        }
+        for (loc, label) in &self.labels {
+            let (x, y) = self.xy(loc.1 * self.dim.0 + loc.0);
+            s += &format!(
+                "<text x=\"{}\" y=\"{}\" font-family=\"sans-serif\" font-size=\"14\" font-weight=\"bold\" text-anchor=\"middle\" fill=\"#fff\" stroke=\"#000\" stroke-width=\"0.6\">{label}</text>\n",
+                x + CELL / 2,
+                y + CELL - 5
+            );
+        }
+        s
+    }
+
+    fn svg_open(&self) -> String {
+        let w = self.dim.0 * CELL + if self.hex { CELL / 2 } else { 0 };
+        let h = self.dim.1 * CELL;
+        format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n")
+    }
+
+    // An SVG that plays the frames through once, `frame_time` apart, and stays on the
+    // last one
+    pub fn svg(&self, frame_time: Duration) -> String {
+        let secs = frame_time.as_secs_f64();
+        let set = |to: &str, n: usize| {
+            format!(
+                "<set attributeName=\"fill\" to=\"{to}\" begin=\"{:.3}s\"/>",
+                n as f64 * secs
+            )
+        };
+        let animate = |i: usize, terrain: bool| {
+            let mut s = String::new();
+            let mut last = Shade::Unseen;
+            for (n, frame) in self.frames.iter().enumerate() {
+                if terrain {
+                    for &(_, t) in frame.edits.iter().filter(|&&(j, _)| j == i) {
+                        s += &set(&terrain_color(t), n);
+                    }
+                } else if frame.shades[i] != last {
+                    last = frame.shades[i];
+                    s += &set(last.color(), n);
+                }
+            }
+            s
+        };
+        format!("{}{}</svg>\n", self.svg_open(), self.grid(animate))
+    }
+
+    // A page with the grid and a slider to step through the frames, that doesn't need
+    // anything besides itself
+    pub fn html(&self, title: &str) -> String {
+        let title = title
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;");
+        let frames: Vec<String> = self
+            .frames
+            .iter()
+            .map(|frame| {
+                format!(
+                    "\"{}\"",
+                    frame.shades.iter().map(|s| s.code()).collect::<String>()
+                )
+            })
+            .collect();
+        let edits: Vec<String> = self
+            .frames
+            .iter()
+            .map(|frame| {
+                let edits: Vec<String> = frame
+                    .edits
+                    .iter()
+                    .map(|&(i, t)| format!("[{i}, \"{}\"]", terrain_color(t)))
+                    .collect();
+                format!("[{}]", edits.join(", "))
+            })
+            .collect();
+        let mut legend = String::new();
+        for (t, name) in &self.legend {
+            legend += &format!(
+                "<span><i style=\"background: {}\"></i>{name} ({})</span>\n",
+                terrain_color(*t),
+                if t.passable() {
+                    t.cost().to_string()
+                } else {
+                    "impassable".into()
+                }
+            );
+        }
+        for (shade, name) in [
+            (Shade::Frontier, "frontier"),
+            (Shade::Visited, "visited"),
+            (Shade::Path, "path"),
+            (Shade::Hazard, "hazard"),
+        ] {
+            legend += &format!(
+                "<span><i class=\"marker\" style=\"background: {}\"></i>{name}</span>\n",
+                shade.color()
+            );
+        }
+        let last = self.frames.len().saturating_sub(1);
+        format!(
+            r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>{title}</title>
+<style>
+body {{ font-family: sans-serif; margin: 1em; }}
+#controls {{ margin: 0.5em 0; }}
+#step {{ width: 30em; vertical-align: middle; }}
+#legend span {{ margin-right: 1em; white-space: nowrap; }}
+#legend i {{ display: inline-block; width: 1em; height: 1em; margin-right: 0.3em; vertical-align: middle; border: 1px solid #999; }}
+#legend i.marker {{ width: 0.6em; height: 0.6em; }}
+</style>
+</head>
+<body>
+<h2>{title}</h2>
+<div id="controls">
+<button id="play">Play</button>
+<input id="step" type="range" min="0" max="{last}" value="{last}">
+<span id="label"></span>
+</div>
+{svg}{grid}</svg>
+<div id="legend">
+{legend}</div>
+<script>
+const frames = [
+{frames}
+];
+const edits = [
+{edits}
+];
+const colors = {{ u: "none", f: "{frontier}", v: "{visited}", p: "{path}", h: "{hazard}" }};
+const terrain = Array.from(document.querySelectorAll("[id^=t]"), (r) => r.getAttribute("fill"));
+const step = document.getElementById("step");
+const label = document.getElementById("label");
+const play = document.getElementById("play");
+let timer = null;
+
+function show(n) {{
+  const fills = terrain.slice();
+  for (let k = 0; k <= n; k++) {{
+    for (const [i, color] of edits[k]) fills[i] = color;
+  }}
+  fills.forEach((color, i) => document.getElementById("t" + i).setAttribute("fill", color));
+  for (let i = 0; i < frames[n].length; i++) {{
+    document.getElementById("m" + i).setAttribute("fill", colors[frames[n][i]]);
+  }}
+  label.textContent = "step " + n + " of " + (frames.length - 1);
+}}
+
+function stop() {{
+  clearInterval(timer);
+  timer = null;
+  play.textContent = "Play";
+}}
+
+step.addEventListener("input", () => {{
+  stop();
+  show(Number(step.value));
+}});
+play.addEventListener("click", () => {{
+  if (timer) return stop();
+  if (Number(step.value) >= frames.length - 1) step.value = 0;
+  play.textContent = "Pause";
+  timer = setInterval(() => {{
+    if (Number(step.value) >= frames.length - 1) return stop();
+    step.value = Number(step.value) + 1;
+    show(Number(step.value));
+  }}, 100);
+}});
+if (frames.length > 0) show({last});
+</script>
+</body>
+</html>
+"#,
+            svg = self.svg_open(),
+            grid = self.grid(|_, _| String::new()),
+            frames = frames.join(",\n"),
+            edits = edits.join(",\n"),
+            frontier = Shade::Frontier.color(),
+            visited = Shade::Visited.color(),
+            path = Shade::Path.color(),
+            hazard = Shade::Hazard.color(),
+        )
+    }
+}
@@ -0,0 +1,204 @@
+use crate::{
+    map::{dist, Map, Status, Terrain, Vec2},
+    search::{Algorithm, Counts, Outcome},
+    trace::Event,
+    Output,
+};
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+// LRTA* (learning real-time A*) for an agent that can't see the whole map. It only
+// knows the terrain within `radius` spots of wherever it's been, and plans as if every
+// spot it hasn't seen costs `unknown_cost`. Each step it moves to the neighbor that
+// looks cheapest to go through, counting the step there and its estimate from there to
+// the goal, and raises its own estimate to that, so it doesn't wander back and forth
+// forever. Heights are known from the start, only the terrain isn't.
+pub struct Explorer {
+    pub name: String,
+    pub title: String,
+    pub label: String,
+    pub failure: String,
+    pub radius: usize,
+    pub unknown_cost: usize,
+}
+
+// What the agent has learned: the map as it thinks it is, where unseen spots are '?'
+// (drawn that way too), and its estimates of the cost from each spot to the goal
+struct Belief {
+    map: Map,
+    seen: Vec<bool>,
+    estimates: Vec<Option<usize>>,
+}
+
+impl Belief {
+    fn new(map: &Map, unknown_cost: usize) -> Self {
+        let mut belief = map.clone();
+        let unknown = Terrain::new('?', Some(unknown_cost));
+        for spot in &mut belief.map {
+            *spot = (unknown, Status::Untraversed);
+        }
+        Self {
+            map: belief,
+            seen: vec![false; map.dim.0 * map.dim.1],
+            estimates: vec![None; map.dim.0 * map.dim.1],
+        }
+    }
+
+    // Look around from `loc`, returning whether anything that turned out impassable came
+    // into view
+    fn sense(&mut self, map: &Map, (x, y): Vec2, radius: usize) -> bool {
+        let mut blocked = false;
+        for sy in y.saturating_sub(radius)..(y + radius + 1).min(map.dim.1) {
+            for sx in x.saturating_sub(radius)..(x + radius + 1).min(map.dim.0) {
+                let i = map.index((sx, sy));
+                if !self.seen[i] {
+                    self.seen[i] = true;
+                    self.map.map[i].0 = map.map[i].0;
+                    blocked |= !map.map[i].0.passable();
+                }
+            }
+        }
+        blocked
+    }
+
+    fn estimate(&self, loc: Vec2) -> usize {
+        let goal = self.map.goal;
+        self.estimates[self.map.index(loc)].unwrap_or_else(|| dist(loc, goal, self.map.dist_mode()))
+    }
+
+    // Whether the goal could still be reached from `loc` if the unseen spots are all
+    // open. It heads for the goal first, since it usually can be.
+    fn reachable(&self, loc: Vec2) -> bool {
+        let (goal, mode) = (self.map.goal, self.map.dist_mode());
+        let mut reached = vec![false; self.seen.len()];
+        let mut q = BinaryHeap::from([Reverse((dist(loc, goal, mode), loc))]);
+        reached[self.map.index(loc)] = true;
+        while let Some(Reverse((_, loc))) = q.pop() {
+            if loc == goal {
+                return true;
+            }
+            for next in self.map.moves(loc) {
+                let i = self.map.index(next);
+                if !reached[i] {
+                    reached[i] = true;
+                    q.push(Reverse((dist(next, goal, mode), next)));
+                }
+            }
+        }
+        false
+    }
+}
+
+impl Explorer {
+    pub fn new(radius: usize, unknown_cost: usize) -> Self {
+        Self {
+            name: "lrta_star".into(),
+            title: "LRTA* search".into(),
+            label: "LRTA*".into(),
+            failure: "LRTA* search failed!".into(),
+            radius: radius.max(1),
+            unknown_cost: unknown_cost.max(1),
+        }
+    }
+}
+
+impl Algorithm for Explorer {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn run(&self, map: &Map, out: &mut Output) -> Outcome {
+        out.text(&format!(
+            "Running {} (sensor radius: {}, unseen spots cost: {})\n",
+            self.title, self.radius, self.unknown_cost
+        ));
+        let mut belief = Belief::new(map, self.unknown_cost);
+        let mut counts = Counts::default();
+        let mut loc = map.start;
+        let mut route = vec![loc];
+        let mut cost = map.map[map.index(loc)].0.cost();
+        belief.sense(map, loc, self.radius);
+
+        let mut reachable = map.at(loc).is_some() && belief.reachable(loc);
+        while reachable && loc != map.goal {
+            belief.map.at_mut(loc).unwrap().1 = Status::Agent(0);
+            out.text(&format!(
+                "Step {}: At ({}, {}) (cost so far: {cost})\n",
+                route.len() - 1,
+                loc.0,
+                loc.1
+            ));
+            out.frame(&belief.map);
+            belief.map.at_mut(loc).unwrap().1 = Status::Path;
+
+            // Go through whichever neighbor looks cheapest, and learn that this spot is
+            // at least that far from the goal
+            counts.pops += 1;
+            let moves = belief.map.moves(loc);
+            counts.generated += moves.len();
+            let Some((through, next)) = moves
+                .into_iter()
+                .map(|next| {
+                    (
+                        belief.map.step_cost(loc, next) + belief.estimate(next),
+                        next,
+                    )
+                })
+                .min()
+            else {
+                reachable = false;
+                break;
+            };
+            let i = map.index(loc);
+            belief.estimates[i] = Some(belief.estimate(loc).max(through));
+            out.event(Event::Pop {
+                loc,
+                g: cost,
+                priority: through as f64,
+            });
+
+            cost += map.step_cost(loc, next);
+            loc = next;
+            route.push(loc);
+            if belief.sense(map, loc, self.radius) && !belief.reachable(loc) {
+                reachable = false;
+            }
+        }
+
+        let seen = belief.seen.iter().filter(|&&seen| seen).count();
+        out.text(&format!(
+            "Saw {seen} of {} spots in {} steps\n",
+            belief.seen.len(),
+            route.len() - 1
+        ));
+        let pops = counts.pops;
+        if !reachable {
+            out.frame(&belief.map);
+            out.event(Event::Fail);
+            out.text(&format!("{} No valid paths exist\n", self.failure));
+            out.flush();
+            return Outcome {
+                path: None,
+                route: vec![],
+                pops,
+                generated: counts.generated,
+                max_frontier: counts.max_frontier,
+            };
+        }
+        belief.map.at_mut(loc).unwrap().1 = Status::Agent(0);
+        out.frame(&belief.map);
+        let dist = route.len();
+        out.event(Event::Path { dist, cost });
+        out.text(&format!(
+            "Path found (dist: {dist} cost: {cost} iterations: {pops}) by {} alg\n",
+            self.label
+        ));
+        out.flush();
+        Outcome {
+            path: Some((dist, cost)),
+            route,
+            pops,
+            generated: counts.generated,
+            max_frontier: counts.max_frontier,
+        }
+    }
+}
@@ -0,0 +1,231 @@
+use crate::{
+    map::{Map, Terrain, Vec2},
+    search::{Algorithm, Strategy},
+    Output,
+};
+use std::{collections::VecDeque, sync::Arc};
+
+// Makes up a map from a seed, so the same seed always gives the same map. Elevation comes
+// from layers of smoothed noise: the lowest ground is lakes, the highest is hills and
+// then mountains, and the rest is field or forest depending on a second layer of noise.
+// Rivers start up high and run downhill until they reach water (or pool into a lake),
+// and then roads are laid between a few towns along the cheapest paths between them.
+pub struct Generator {
+    pub width: usize,
+    pub height: usize,
+    pub seed: u64,
+    pub connected: bool,     // whether the start and goal have to be connected
+    pub feature: f64,        // about how many spots across the biggest hills and lakes are
+    pub lake_level: f64,     // elevations (from 0 to 1) below this are water
+    pub hill_level: f64,     // and above this are hills
+    pub mountain_level: f64, // and above this are mountains
+    pub rivers: usize,
+    pub towns: usize,
+}
+
+// A small, fast random number generator (splitmix64), so the maps don't depend on anything
+// but the seed
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        mix(self.0)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+// Noise that's random at whole coordinates and smoothly blended in between, from 0 to 1
+fn value_noise(seed: u64, x: f64, y: f64) -> f64 {
+    let corner = |cx: f64, cy: f64| {
+        let h = mix(seed ^ mix((cx as i64 as u64) ^ mix(cy as i64 as u64)));
+        (h >> 11) as f64 / (1u64 << 53) as f64
+    };
+    let (x0, y0) = (x.floor(), y.floor());
+    let smooth = |t: f64| t * t * (3.0 - 2.0 * t);
+    let (tx, ty) = (smooth(x - x0), smooth(y - y0));
+    let top = corner(x0, y0) * (1.0 - tx) + corner(x0 + 1.0, y0) * tx;
+    let bottom = corner(x0, y0 + 1.0) * (1.0 - tx) + corner(x0 + 1.0, y0 + 1.0) * tx;
+    top * (1.0 - ty) + bottom * ty
+}
+
+// Several octaves of noise, each twice as fine and half as strong as the last, scaled
+// to cover 0 to 1 over the whole map
+fn noise_field(seed: u64, width: usize, height: usize, feature: f64) -> Vec<Vec<f64>> {
+    let mut field = vec![vec![0.0; width]; height];
+    for (y, row) in field.iter_mut().enumerate() {
+        for (x, value) in row.iter_mut().enumerate() {
+            let mut amp = 1.0;
+            let mut freq = 1.0 / feature;
+            for octave in 0..4 {
+                *value += amp * value_noise(seed + octave, x as f64 * freq, y as f64 * freq);
+                amp /= 2.0;
+                freq *= 2.0;
+            }
+        }
+    }
+    let lowest = field.iter().flatten().copied().fold(f64::MAX, f64::min);
+    let highest = field.iter().flatten().copied().fold(f64::MIN, f64::max);
+    let range = (highest - lowest).max(f64::EPSILON);
+    for value in field.iter_mut().flatten() {
+        *value = (*value - lowest) / range;
+    }
+    field
+}
+
+impl Generator {
+    pub fn new(width: usize, height: usize, seed: u64) -> Self {
+        let area = width * height;
+        Self {
+            width,
+            height,
+            seed,
+            connected: false,
+            feature: (width.max(height) as f64 / 3.0).max(2.0),
+            lake_level: 0.25,
+            hill_level: 0.65,
+            mountain_level: 0.8,
+            rivers: (area / 200).clamp(1, 16),
+            towns: (area / 200).clamp(3, 10),
+        }
+    }
+
+    pub fn generate(&self) -> Map {
+        let (w, h) = (self.width.max(1), self.height.max(1));
+        let mut rng = Rng(self.seed);
+        let elevation = noise_field(mix(self.seed), w, h, self.feature);
+        let moisture = noise_field(mix(self.seed ^ 1), w, h, self.feature / 2.0);
+
+        // Terrain by elevation, and forest where it's wet
+        let mut rows: Vec<Vec<Terrain>> = (0..h)
+            .map(|y| {
+                (0..w)
+                    .map(|x| match elevation[y][x] {
+                        e if e < self.lake_level => Terrain::WATER,
+                        e if e >= self.mountain_level => Terrain::MOUNTAINS,
+                        e if e >= self.hill_level => Terrain::HILLS,
+                        _ if moisture[y][x] > 0.65 => Terrain::FOREST,
+                        _ => Terrain::FIELD,
+                    })
+                    .collect()
+            })
+            .collect();
+        let neighbors = |(x, y): Vec2| {
+            [(0, -1), (1, 0), (0, 1), (-1, 0)]
+                .into_iter()
+                .filter_map(move |(dx, dy)| {
+                    let loc = (x.checked_add_signed(dx)?, y.checked_add_signed(dy)?);
+                    (loc.0 < w && loc.1 < h).then_some(loc)
+                })
+        };
+
+        // Rivers run downhill from high ground until they reach water or another river,
+        // or pool into a lake where there's nowhere lower to go
+        for _ in 0..self.rivers {
+            let mut loc = (rng.below(w), rng.below(h));
+            for _ in 0..8 {
+                let other = (rng.below(w), rng.below(h));
+                if elevation[other.1][other.0] > elevation[loc.1][loc.0] {
+                    loc = other;
+                }
+            }
+            loop {
+                rows[loc.1][loc.0] = Terrain::RIVER;
+                let Some(next) = neighbors(loc)
+                    .min_by(|a, b| elevation[a.1][a.0].total_cmp(&elevation[b.1][b.0]))
+                else {
+                    break;
+                };
+                if elevation[next.1][next.0] >= elevation[loc.1][loc.0] {
+                    rows[loc.1][loc.0] = Terrain::WATER;
+                    break;
+                }
+                if matches!(rows[next.1][next.0], Terrain::WATER | Terrain::RIVER) {
+                    break;
+                }
+                loc = next;
+            }
+        }
+
+        // Make sure there's somewhere to stand
+        let land = |rows: &Vec<Vec<Terrain>>| -> Vec<Vec2> {
+            (0..h)
+                .flat_map(|y| (0..w).map(move |x| (x, y)))
+                .filter(|&(x, y)| rows[y][x].passable())
+                .collect()
+        };
+        if land(&rows).is_empty() {
+            rows[0][0] = Terrain::FIELD;
+            rows[h - 1][w - 1] = Terrain::FIELD;
+        }
+
+        // Roads follow the cheapest path from each town to the next, so they go around
+        // mountains, bridge rivers, and join up with the roads that are already there
+        let terrains = Arc::default();
+        let spots = land(&rows);
+        let towns: Vec<Vec2> = (0..self.towns)
+            .map(|_| spots[rng.below(spots.len())])
+            .collect();
+        for pair in towns.windows(2) {
+            if pair[0] == pair[1] {
+                continue;
+            }
+            let map = Map::new(rows.clone(), pair[0], pair[1], Arc::clone(&terrains));
+            let outcome = Strategy::lowest_cost_path().run(&map, &mut Output::silent());
+            for (x, y) in outcome.route {
+                rows[y][x] = Terrain::ROAD;
+            }
+        }
+
+        // Pick the start and goal, from the same stretch of land if they have to be connected
+        let mut spots = land(&rows);
+        if self.connected {
+            spots = Self::largest_region(&rows, neighbors);
+        }
+        let start = spots[rng.below(spots.len())];
+        let goal = spots[rng.below(spots.len())];
+        Map::new(rows, start, goal, terrains)
+    }
+
+    // The biggest group of passable spots that can all reach each other
+    fn largest_region<I: Iterator<Item = Vec2>>(
+        rows: &[Vec<Terrain>],
+        neighbors: impl Fn(Vec2) -> I,
+    ) -> Vec<Vec2> {
+        let (w, h) = (rows[0].len(), rows.len());
+        let mut seen = vec![vec![false; w]; h];
+        let mut largest = vec![];
+        for y in 0..h {
+            for x in 0..w {
+                if seen[y][x] || !rows[y][x].passable() {
+                    continue;
+                }
+                let mut region = vec![];
+                let mut q = VecDeque::from([(x, y)]);
+                seen[y][x] = true;
+                while let Some(loc) = q.pop_front() {
+                    region.push(loc);
+                    for (nx, ny) in neighbors(loc) {
+                        if !seen[ny][nx] && rows[ny][nx].passable() {
+                            seen[ny][nx] = true;
+                            q.push_back((nx, ny));
+                        }
+                    }
+                }
+                if region.len() > largest.len() {
+                    largest = region;
+                }
+            }
+        }
+        largest
+    }
+}
@@ -0,0 +1,409 @@
+use crate::{
+    map::{dist, Map, Status, Vec2},
+    search::{search, Algorithm, Counts, Outcome, Strategy},
+    trace::Event,
+    Output,
+};
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+    time::{Duration, Instant},
+};
+
+// HPA* (hierarchical path-finding A*): the map is cut into square clusters, and the
+// spots where a cluster can be crossed into the next one become the nodes of a much
+// smaller graph, joined by the cheapest paths between them inside each cluster. That
+// graph only has to be built once per map. A query hooks the start and goal up to the
+// nodes of their clusters, searches the small graph, and then fills in each leg with a
+// search inside one cluster. Paths that would leave and come back into a cluster can't
+// be found, so they can cost a little more than the cheapest path.
+pub struct Hierarchical {
+    pub name: String,
+    pub title: String,
+    pub label: String,
+    pub failure: String,
+    pub graph: Abstraction,
+    pub build_time: Duration, // how long building the graph took
+    pub compare: bool,        // also time plain A* on the same query, to show the speedup
+}
+
+pub struct Abstraction {
+    pub cluster: usize, // spots across a cluster (the ones at the edges can be smaller)
+    pub nodes: Vec<Vec2>, // spots on either side of an entrance between two clusters
+    edges: Vec<Vec<(usize, usize)>>, // (node, cost) for each node's ways out
+    pub searches: usize, // how many searches inside clusters it took to build
+}
+
+// Entrances at least this wide get two crossings instead of one
+const WIDE_ENTRANCE: usize = 6;
+
+impl Abstraction {
+    pub fn build(map: &Map, cluster: usize) -> Self {
+        // hex clusters have to start on even rows for the rows inside them to line up
+        let mut cluster = cluster.max(1);
+        if map.hex {
+            cluster += cluster % 2;
+        }
+        let mut graph = Self {
+            cluster,
+            nodes: vec![],
+            edges: vec![],
+            searches: 0,
+        };
+        let mut node_at = HashMap::new();
+        let mut node = |graph: &mut Self, loc: Vec2| {
+            *node_at.entry(loc).or_insert_with(|| {
+                graph.nodes.push(loc);
+                graph.edges.push(vec![]);
+                graph.nodes.len() - 1
+            })
+        };
+
+        // Entrances: runs of spots along the border between two clusters that can be
+        // crossed straight over, to the next cluster right or the next one down
+        let crosses = |a: Vec2, b: Vec2| map.at(a).is_some() && map.moves(a).contains(&b);
+        let mut entrances: Vec<Vec<(Vec2, Vec2)>> = vec![];
+        for x in (cluster..map.dim.0).step_by(cluster) {
+            let mut run: Vec<(Vec2, Vec2)> = vec![];
+            for y in 0..map.dim.1 {
+                if y % cluster == 0 || !crosses((x - 1, y), (x, y)) {
+                    entrances.extend((!run.is_empty()).then(|| std::mem::take(&mut run)));
+                }
+                if crosses((x - 1, y), (x, y)) {
+                    run.push(((x - 1, y), (x, y)));
+                }
+            }
+            entrances.extend((!run.is_empty()).then_some(run));
+        }
+        for y in (cluster..map.dim.1).step_by(cluster) {
+            let mut run: Vec<(Vec2, Vec2)> = vec![];
+            for x in 0..map.dim.0 {
+                if x % cluster == 0 || !crosses((x, y - 1), (x, y)) {
+                    entrances.extend((!run.is_empty()).then(|| std::mem::take(&mut run)));
+                }
+                if crosses((x, y - 1), (x, y)) {
+                    run.push(((x, y - 1), (x, y)));
+                }
+            }
+            entrances.extend((!run.is_empty()).then_some(run));
+        }
+        for run in entrances {
+            // the cheapest crossing (the one nearest the middle of those), or in each
+            // half of a wide entrance
+            let cheapest = |part: &[(Vec2, Vec2)]| {
+                let mid = part.len() / 2;
+                *(part.iter().enumerate())
+                    .min_by_key(|&(k, &(a, b))| {
+                        (map.step_cost(a, b) + map.step_cost(b, a), k.abs_diff(mid))
+                    })
+                    .unwrap()
+                    .1
+            };
+            let picks = match run.len() {
+                n if n >= WIDE_ENTRANCE => vec![cheapest(&run[..n / 2]), cheapest(&run[n / 2..])],
+                _ => vec![cheapest(&run)],
+            };
+            for (a, b) in picks {
+                let (i, j) = (node(&mut graph, a), node(&mut graph, b));
+                graph.edges[i].push((j, map.step_cost(a, b)));
+                graph.edges[j].push((i, map.step_cost(b, a)));
+            }
+        }
+
+        // The cheapest way between every two nodes in a cluster, staying inside it
+        let mut by_cluster: HashMap<Vec2, Vec<usize>> = HashMap::new();
+        for (i, &loc) in graph.nodes.iter().enumerate() {
+            by_cluster.entry(graph.cluster_of(loc)).or_default().push(i);
+        }
+        let strategy = Strategy::lowest_cost_path();
+        for (c, members) in by_cluster {
+            let (origin, mut local) = graph.crop(map, c);
+            for &i in &members {
+                for &j in members.iter().filter(|&&j| j != i) {
+                    let (from, to) = (graph.nodes[i], graph.nodes[j]);
+                    graph.searches += 1;
+                    if let Some((_, cost)) = inside(&mut local, origin, from, to, &strategy, None) {
+                        graph.edges[i].push((j, cost));
+                    }
+                }
+            }
+        }
+        graph
+    }
+
+    pub fn cluster_of(&self, loc: Vec2) -> Vec2 {
+        (loc.0 / self.cluster, loc.1 / self.cluster)
+    }
+
+    pub fn entrances(&self) -> usize {
+        self.nodes.len()
+    }
+
+    // A cluster as a map of its own, and where it is in the whole map
+    fn crop(&self, map: &Map, c: Vec2) -> (Vec2, Map) {
+        let origin = (c.0 * self.cluster, c.1 * self.cluster);
+        let dim = (
+            self.cluster.min(map.dim.0 - origin.0),
+            self.cluster.min(map.dim.1 - origin.1),
+        );
+        (origin, map.crop(origin, dim))
+    }
+
+    // The path from `start` to `goal`, spot by spot, and what it costs (counting the
+    // start's terrain like the other searches do). Only the search over the nodes is
+    // traced, not the ones inside clusters.
+    pub fn query(
+        &self,
+        map: &Map,
+        start: Vec2,
+        goal: Vec2,
+        counts: &mut Counts,
+        out: &mut Output,
+    ) -> Option<(Vec<Vec2>, usize)> {
+        map.at(start)?;
+        map.at(goal)?;
+        let strategy = Strategy::lowest_cost_path();
+        let n = self.nodes.len();
+        let (s, g) = (n, n + 1);
+        let spot = |i: usize| match i {
+            i if i == s => start,
+            i if i == g => goal,
+            i => self.nodes[i],
+        };
+
+        // Hook the start and goal up to the nodes of their clusters, and to each other
+        // if they share one
+        let (start_cluster, goal_cluster) = (self.cluster_of(start), self.cluster_of(goal));
+        let (s_origin, mut s_local) = self.crop(map, start_cluster);
+        let (g_origin, mut g_local) = self.crop(map, goal_cluster);
+        let mut from_start = vec![];
+        let mut to_goal = HashMap::new();
+        for (i, &loc) in self.nodes.iter().enumerate() {
+            if self.cluster_of(loc) == start_cluster {
+                let leg = inside(&mut s_local, s_origin, start, loc, &strategy, Some(counts));
+                from_start.extend(leg.map(|(_, cost)| (i, cost)));
+            }
+            if self.cluster_of(loc) == goal_cluster {
+                let leg = inside(&mut g_local, g_origin, loc, goal, &strategy, Some(counts));
+                to_goal.extend(leg.map(|(_, cost)| (i, cost)));
+            }
+        }
+        if start_cluster == goal_cluster {
+            let leg = inside(&mut s_local, s_origin, start, goal, &strategy, Some(counts));
+            from_start.extend(leg.map(|(_, cost)| (g, cost)));
+        }
+        let ways_out = |i: usize| -> Vec<(usize, usize)> {
+            match i {
+                i if i == s => from_start.clone(),
+                i if i == g => vec![],
+                i => {
+                    let mut out = self.edges[i].clone();
+                    out.extend(to_goal.get(&i).map(|&cost| (g, cost)));
+                    out
+                }
+            }
+        };
+
+        // A* over the nodes
+        let mode = map.dist_mode();
+        let mut best = vec![usize::MAX; n + 2];
+        let mut parent = vec![usize::MAX; n + 2];
+        let mut q = BinaryHeap::new();
+        let start_cost = map.map[map.index(start)].0.cost();
+        best[s] = 0;
+        q.push(Reverse((dist(start, goal, mode), s)));
+        counts.pushed(q.len());
+        out.event(Event::Push {
+            loc: start,
+            from: None,
+            g: start_cost,
+            priority: dist(start, goal, mode) as f64,
+        });
+        while let Some(Reverse((f, i))) = q.pop() {
+            if f > best[i] + dist(spot(i), goal, mode) {
+                continue;
+            }
+            counts.pops += 1;
+            out.event(Event::Pop {
+                loc: spot(i),
+                g: start_cost + best[i],
+                priority: f as f64,
+            });
+            if i == g {
+                out.event(Event::Goal {
+                    loc: goal,
+                    g: start_cost + best[i],
+                });
+                break;
+            }
+            for (j, cost) in ways_out(i) {
+                if best[i] + cost < best[j] {
+                    let known = best[j];
+                    best[j] = best[i] + cost;
+                    parent[j] = i;
+                    let f = best[j] + dist(spot(j), goal, mode);
+                    q.push(Reverse((f, j)));
+                    counts.pushed(q.len());
+                    let g_new = start_cost + best[j];
+                    out.event(Event::reached(known, spot(j), spot(i), g_new, f as f64));
+                }
+            }
+        }
+        if best[g] == usize::MAX {
+            return None;
+        }
+
+        // Fill in each leg: a step across an entrance, or a search inside a cluster
+        let mut hops = vec![g];
+        while let Some(&i) = hops.last().filter(|&&i| i != s) {
+            hops.push(parent[i]);
+        }
+        hops.reverse();
+        let mut route = vec![start];
+        for pair in hops.windows(2) {
+            let (from, to) = (spot(pair[0]), spot(pair[1]));
+            let c = self.cluster_of(from);
+            if c != self.cluster_of(to) {
+                route.push(to);
+                continue;
+            }
+            let (origin, mut local) = self.crop(map, c);
+            let (leg, _) = inside(&mut local, origin, from, to, &strategy, Some(counts))?;
+            route.extend(&leg[1..]);
+        }
+        let cost = map.map[map.index(start)].0.cost()
+            + route
+                .windows(2)
+                .map(|pair| map.step_cost(pair[0], pair[1]))
+                .sum::<usize>();
+        Some((route, cost))
+    }
+}
+
+// The lowest cost path from `from` to `to` without leaving the cluster `local` was cut
+// from at `origin`, and what it costs not counting the spot it starts on
+fn inside(
+    local: &mut Map,
+    origin: Vec2,
+    from: Vec2,
+    to: Vec2,
+    strategy: &Strategy,
+    counts: Option<&mut Counts>,
+) -> Option<(Vec<Vec2>, usize)> {
+    local.start = (from.0 - origin.0, from.1 - origin.1);
+    local.goal = (to.0 - origin.0, to.1 - origin.1);
+    let outcome = search(local, strategy, &mut Output::silent());
+    if let Some(counts) = counts {
+        counts.add(&outcome);
+    }
+    let (_, cost) = outcome.path?;
+    let route = outcome
+        .route
+        .iter()
+        .map(|&(x, y)| (x + origin.0, y + origin.1))
+        .collect();
+    Some((route, cost - local.map[local.index(local.start)].0.cost()))
+}
+
+impl Hierarchical {
+    // Builds the graph for the map, with clusters `cluster` spots across
+    pub fn new(map: &Map, cluster: usize) -> Self {
+        let clock = Instant::now();
+        let graph = Abstraction::build(map, cluster);
+        Self {
+            name: "hpa_star".into(),
+            title: "hierarchical A* search".into(),
+            label: "HPA*".into(),
+            failure: "HPA* search failed!".into(),
+            graph,
+            build_time: clock.elapsed(),
+            compare: false,
+        }
+    }
+}
+
+fn ms(time: Duration) -> f64 {
+    time.as_secs_f64() * 1000.0
+}
+
+impl Algorithm for Hierarchical {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn run(&self, map: &Map, out: &mut Output) -> Outcome {
+        let c = self.graph.cluster;
+        out.text(&format!(
+            "Running {} (clusters: {c}x{c}, entrances: {})\n",
+            self.title,
+            self.graph.entrances()
+        ));
+        out.text(&format!(
+            "Built the graph in {:.3} ms ({} searches inside clusters)\n",
+            ms(self.build_time),
+            self.graph.searches
+        ));
+        let mut counts = Counts::default();
+        let clock = Instant::now();
+        let plan = self.graph.query(map, map.start, map.goal, &mut counts, out);
+        let query_time = clock.elapsed();
+
+        // Time plain A* on the same query to compare
+        if self.compare {
+            let plain = match (map.hex, map.diagonals) {
+                (true, _) => Strategy::a_star_hex(),
+                (false, Some(diagonal)) => Strategy::a_star_octile(diagonal),
+                (false, None) => Strategy::a_star_taxicab(),
+            };
+            let clock = Instant::now();
+            let plain_outcome = plain.run(map, &mut Output::silent());
+            let plain_time = clock.elapsed();
+            out.text(&format!(
+                "Query took {:.3} ms, {} took {:.3} ms ({:.1}x speedup, {} pops against {})\n",
+                ms(query_time),
+                plain.name,
+                ms(plain_time),
+                plain_time.as_secs_f64() / query_time.as_secs_f64().max(1e-9),
+                counts.pops,
+                plain_outcome.pops
+            ));
+        } else {
+            out.text(&format!("Query took {:.3} ms\n", ms(query_time)));
+        }
+
+        let pops = counts.pops;
+        let Some((route, cost)) = plan else {
+            out.event(Event::Fail);
+            out.text(&format!("{} No valid paths exist\n", self.failure));
+            out.flush();
+            return Outcome {
+                path: None,
+                route: vec![],
+                pops,
+                generated: counts.generated,
+                max_frontier: counts.max_frontier,
+            };
+        };
+        if out.wants_frames() {
+            let mut view = map.clone();
+            for &loc in &route {
+                view.at_mut(loc).unwrap().1 = Status::Path;
+            }
+            out.frame(&view);
+        }
+        let dist = route.len();
+        out.event(Event::Path { dist, cost });
+        out.text(&format!(
+            "Path found (dist: {dist} cost: {cost} iterations: {pops}) by {} alg\n",
+            self.label
+        ));
+        out.flush();
+        Outcome {
+            path: Some((dist, cost)),
+            route,
+            pops,
+            generated: counts.generated,
+            max_frontier: counts.max_frontier,
+        }
+    }
+}
@@ -0,0 +1,236 @@
+use crate::{
+    map::{DistMode, Map, Status, Vec2},
+    search::{report, Algorithm, Counts, Heuristic, Outcome},
+    trace::Event,
+    Output,
+};
+use priority_queue::PriorityQueue;
+use std::cmp::Reverse;
+
+// Jump point search: A* that only expands "jump points". Inside a region of spots
+// that all cost the same (and are all as high), the paths through it are symmetric, so
+// a search can jump straight across instead of expanding every spot on the way. A jump
+// stops at the goal and at any spot touching a different terrain (or water) or height,
+// where the search falls back to expanding every neighbor like A* would.
+//
+// Jumps follow a horizontal-first ordering: horizontal jumps peek up and down from
+// every spot they pass, and stop wherever one of those vertical jumps would. Whether a
+// vertical jump finds anything is the same from every spot along it, so that's
+// remembered for the whole search, and each column is only scanned once each way.
+// With diagonal moves allowed, or on a hex map, there's no jumping, and it's plain A*.
+pub struct JumpPoint {
+    pub name: String,
+    pub title: String,
+    pub label: String,
+    pub failure: String,
+    pub heuristic: Heuristic,
+}
+
+impl JumpPoint {
+    pub fn new(mode: DistMode) -> Self {
+        let dist_name = match mode {
+            DistMode::TaxiCab => "taxicab",
+            DistMode::Euclidean => "euclidean",
+            DistMode::Octile(_) => "octile",
+            DistMode::Hex => "hex",
+        };
+        Self {
+            name: "jump_point".into(),
+            title: format!("jump point search (heuristic: {dist_name} dist)"),
+            label: "jump point".into(),
+            failure: "Jump point search failed!".into(),
+            heuristic: Heuristic::Dist(mode),
+        }
+    }
+
+    // Whether anything around a spot (diagonals too, since a corner that opens up
+    // behind a jump forces a turn) is something other than open terrain costing `cost`
+    // at the spot's own height
+    fn at_boundary(map: &Map, loc: Vec2, cost: usize) -> bool {
+        (-1..=1)
+            .flat_map(|dx| (-1..=1).map(move |dy| (dx, dy)))
+            .any(|(dx, dy)| {
+                let (Some(x), Some(y)) =
+                    (loc.0.checked_add_signed(dx), loc.1.checked_add_signed(dy))
+                else {
+                    return false;
+                };
+                // the edge of the map never ends, so it can't force a turn
+                x < map.dim.0
+                    && y < map.dim.1
+                    && (map.at((x, y)).is_none_or(|(t, _)| t.cost() != cost)
+                        || map.height((x, y)) != map.height(loc))
+            })
+    }
+
+    // Whether a vertical jump from `from` (`up` or down) reaches a jump point, using and
+    // filling in `seen` for every spot it passes: two entries per spot, up then down
+    fn probe(map: &Map, from: Vec2, up: bool, seen: &mut [Option<bool>]) -> bool {
+        let slot = |loc: Vec2| map.index(loc) * 2 + !up as usize;
+        let d = if up { (0, -1) } else { (0, 1) };
+        let mut passed = vec![from];
+        let found = loop {
+            let loc = *passed.last().unwrap();
+            if let Some(found) = seen[slot(loc)] {
+                break found;
+            }
+            let Some((next, (terrain, _))) =
+                map.go(loc, d).and_then(|next| Some((next, map.at(next)?)))
+            else {
+                break false;
+            };
+            if next == map.goal || Self::at_boundary(map, next, terrain.cost()) {
+                break true;
+            }
+            passed.push(next);
+        };
+        for loc in passed {
+            seen[slot(loc)] = Some(found);
+        }
+        found
+    }
+
+    // Jump from `from` in direction `d`, returning the jump point reached and the cost
+    // of getting there, or None if the jump runs into a dead end
+    fn jump(
+        map: &Map,
+        from: Vec2,
+        d: (isize, isize),
+        seen: &mut [Option<bool>],
+    ) -> Option<(Vec2, usize)> {
+        let mut loc = map.go(from, d)?;
+        if map.diagonals.is_some() || map.hex {
+            return Some((loc, map.step_cost(from, loc)));
+        }
+        // the first step can climb onto the region, but every one after that is level
+        let first = map.step_cost(from, loc);
+        let cost = map.at(loc)?.0.cost();
+        let mut steps = 0;
+        loop {
+            if loc == map.goal || Self::at_boundary(map, loc, cost) {
+                return Some((loc, first + steps * cost));
+            }
+            if d.0 != 0 && (Self::probe(map, loc, true, seen) || Self::probe(map, loc, false, seen))
+            {
+                return Some((loc, first + steps * cost));
+            }
+            // not at a boundary, so the next spot (if on the map) costs the same
+            loc = map.go(loc, d)?;
+            steps += 1;
+        }
+    }
+}
+
+impl Algorithm for JumpPoint {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn run(&self, map: &Map, out: &mut Output) -> Outcome {
+        // Variables
+        let mut done = false;
+        let mut view = map.clone();
+        let (start, goal) = (map.start, map.goal);
+        let size = map.dim.0 * map.dim.1;
+        let mut g = vec![usize::MAX; size];
+        let mut from: Vec<Option<Vec2>> = vec![None; size];
+        let mut q = PriorityQueue::new();
+        let mut counts = Counts::default();
+        let mut seen = vec![None; size * 2];
+
+        // Initialization
+        view.costs = vec![usize::MAX; map.dim.0 * map.dim.1];
+        view.display_costs = true;
+        view.at_mut(start).unwrap().1 = Status::Path;
+        g[map.index(start)] = map.at(start).unwrap().0.cost();
+        view.costs[map.index(start)] = g[map.index(start)];
+        q.push(start, Reverse(0));
+        counts.pushed(q.len());
+        out.event(Event::Push {
+            loc: start,
+            from: None,
+            g: g[map.index(start)],
+            priority: 0.0,
+        });
+
+        // Loop
+        out.text(&format!("Running {}\n", self.title));
+        while let Some((loc, Reverse(f))) = q.pop() {
+            counts.pops += 1;
+            out.event(Event::Pop {
+                loc,
+                g: g[map.index(loc)],
+                priority: f as f64,
+            });
+            out.frame(&view);
+            view.at_mut(loc).unwrap().1.deactivate();
+            out.event(Event::Deactivate { loc });
+            if loc == goal {
+                out.event(Event::Goal {
+                    loc,
+                    g: g[map.index(loc)],
+                });
+                done = true;
+                break;
+            }
+
+            // Jump in every direction, and queue up the jump points that got cheaper
+            let cost = g[map.index(loc)];
+            for d in map.directions(loc) {
+                let Some((loc_new, jump_cost)) = Self::jump(map, loc, d, &mut seen) else {
+                    continue;
+                };
+                let i = map.index(loc_new);
+                let maybe_cost = cost + jump_cost;
+                if maybe_cost < g[i] {
+                    let f = maybe_cost.saturating_add(self.heuristic.estimate(loc_new, goal));
+                    out.event(Event::reached(g[i], loc_new, loc, maybe_cost, f as f64));
+                    g[i] = maybe_cost;
+                    from[i] = Some(loc);
+                    q.push(loc_new, Reverse(f));
+                    counts.pushed(q.len());
+                    // point back along the jump, at the spot it came through
+                    let prev = (
+                        (loc_new.0 as isize - d.0) as usize,
+                        (loc_new.1 as isize - d.1) as usize,
+                    );
+                    view.at_mut(loc_new).unwrap().1 = map.toward(loc_new, prev, true);
+                    view.costs[map.index(loc_new)] = maybe_cost;
+                }
+            }
+        }
+
+        // Fill the jumps back in to get the whole path
+        for (loc, _) in q {
+            view.at_mut(loc).unwrap().1.deactivate();
+            out.event(Event::Deactivate { loc });
+        }
+        if done {
+            let mut path = vec![goal];
+            let mut loc = goal;
+            while let Some(prev) = from[map.index(loc)] {
+                let d = (
+                    (prev.0 as isize - loc.0 as isize).signum(),
+                    (prev.1 as isize - loc.1 as isize).signum(),
+                );
+                while loc != prev {
+                    loc = (
+                        (loc.0 as isize + d.0) as usize,
+                        (loc.1 as isize + d.1) as usize,
+                    );
+                    path.push(loc);
+                }
+            }
+            path.reverse();
+            view.trail(&path);
+            out.frame(&view);
+        }
+        report(
+            done.then_some(&mut view),
+            &self.label,
+            &self.failure,
+            counts,
+            out,
+        )
+    }
+}
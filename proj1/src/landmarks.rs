@@ -0,0 +1,205 @@
+use crate::map::{Map, Vec2};
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap,
+    io::{Read, Write},
+};
+
+// ALT (A*, landmarks and the triangle inequality): exact costs to and from a few
+// landmark spots, worked out ahead of time. Going from a spot to the goal can't cost
+// less than going from the spot to a landmark minus going from the goal to it, or than
+// going from the landmark to the goal minus going from it to the spot. Unlike the
+// distance heuristics, those bounds know about the terrain in between.
+pub struct Landmarks {
+    pub spots: Vec<Vec2>,
+    dim: Vec2,
+    from: Vec<Vec<usize>>, // from[i][spot]: the cost from landmark i to the spot
+    to: Vec<Vec<usize>>,   // to[i][spot]: the cost from the spot to landmark i
+    fingerprint: u64,
+}
+
+const MAGIC: &[u8; 4] = b"ALT1";
+const UNREACHABLE: u32 = u32::MAX;
+
+// Cheapest costs between `source` and every spot, not counting the source's terrain.
+// Forward it's the cost from the source to each spot, and backward it's from each spot
+// to the source. (Moves are allowed both ways, but cost what the spot moved onto does.)
+fn dijkstra(map: &Map, source: Vec2, forward: bool) -> Vec<usize> {
+    let mut dist = vec![usize::MAX; map.dim.0 * map.dim.1];
+    let mut q = BinaryHeap::new();
+    dist[map.index(source)] = 0;
+    q.push(Reverse((0, source)));
+    while let Some(Reverse((d, loc))) = q.pop() {
+        if d > dist[map.index(loc)] {
+            continue;
+        }
+        for next in map.moves(loc) {
+            let step = match forward {
+                true => map.step_cost(loc, next),
+                false => map.step_cost(next, loc),
+            };
+            let i = map.index(next);
+            if d + step < dist[i] {
+                dist[i] = d + step;
+                q.push(Reverse((d + step, next)));
+            }
+        }
+    }
+    dist
+}
+
+// A hash of everything the costs depend on (FNV-1a), so a saved table can be checked
+// against the map it's loaded for
+fn fingerprint(map: &Map) -> u64 {
+    let mut hash: u64 = 0xCBF2_9CE4_8422_2325;
+    let mut feed = |n: u64| {
+        for byte in n.to_le_bytes() {
+            hash = (hash ^ byte as u64).wrapping_mul(0x0100_0000_01B3);
+        }
+    };
+    feed(map.dim.0 as u64);
+    feed(map.dim.1 as u64);
+    feed(map.diagonals.map_or(0, f64::to_bits));
+    if map.hex {
+        feed(6); // moves a spot
+    }
+    if !map.elevation.is_empty() {
+        feed(map.climb as u64);
+        map.elevation.iter().for_each(|&h| feed(h as u64));
+    }
+    for spot in &map.map {
+        feed(spot.0.symbol() as u64);
+        feed(spot.0.cost() as u64);
+    }
+    hash
+}
+
+impl Landmarks {
+    // Pick `count` landmarks spread out over the map: each one is the spot that's the
+    // most expensive to reach from all the ones before it. They don't depend on the
+    // start or goal, so one table works for any search on the map.
+    pub fn build(map: &Map, count: usize) -> Self {
+        let mut table = Self {
+            spots: vec![],
+            dim: map.dim,
+            from: vec![],
+            to: vec![],
+            fingerprint: fingerprint(map),
+        };
+        let spots = (0..map.dim.1).flat_map(|y| (0..map.dim.0).map(move |x| (x, y)));
+        let Some(first) = spots.clone().find(|&loc| map.at(loc).is_some()) else {
+            return table;
+        };
+        // how cheaply the nearest landmark reaches each spot
+        let mut nearest = dijkstra(map, first, true);
+        for _ in 0..count {
+            let Some(loc) = spots
+                .clone()
+                .filter(|&loc| nearest[map.index(loc)] != usize::MAX)
+                .max_by_key(|&loc| nearest[map.index(loc)])
+            else {
+                break;
+            };
+            if nearest[map.index(loc)] == 0 && !table.spots.is_empty() {
+                break; // every spot it can reach is already a landmark
+            }
+            let from = dijkstra(map, loc, true);
+            for (n, &d) in nearest.iter_mut().zip(&from) {
+                *n = (*n).min(d);
+            }
+            table.spots.push(loc);
+            table.from.push(from);
+            table.to.push(dijkstra(map, loc, false));
+        }
+        table
+    }
+
+    // A lower bound on the cost from `loc` to `goal`
+    pub fn estimate(&self, loc: Vec2, goal: Vec2) -> usize {
+        let (v, t) = (loc.1 * self.dim.0 + loc.0, goal.1 * self.dim.0 + goal.0);
+        let mut best = 0;
+        for (from, to) in self.from.iter().zip(&self.to) {
+            if to[v] != usize::MAX && to[t] != usize::MAX {
+                best = best.max(to[v].saturating_sub(to[t]));
+            }
+            if from[t] != usize::MAX && from[v] != usize::MAX {
+                best = best.max(from[t].saturating_sub(from[v]));
+            }
+        }
+        best
+    }
+
+    // The table saved for this map at `path` if there is one and it's still good,
+    // or else a new one, which gets saved there for next time
+    pub fn for_map(map: &Map, count: usize, path: Option<&str>) -> Self {
+        if let Some(table) = path.and_then(|path| Self::load(path, map)) {
+            if table.spots.len() == count {
+                return table;
+            }
+        }
+        let table = Self::build(map, count);
+        if let Some(path) = path {
+            // it's only a cache, so if it can't be written it just gets built next time
+            let _ = table.save(path);
+        }
+        table
+    }
+
+    // Saved as the magic bytes, the fingerprint, the size and the number of landmarks,
+    // then each landmark's spot and costs, all as little endian numbers
+    pub fn save(&self, path: &str) -> std::io::Result<()> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend(self.fingerprint.to_le_bytes());
+        for n in [self.dim.0, self.dim.1, self.spots.len()] {
+            bytes.extend((n as u32).to_le_bytes());
+        }
+        for (i, &(x, y)) in self.spots.iter().enumerate() {
+            bytes.extend((x as u32).to_le_bytes());
+            bytes.extend((y as u32).to_le_bytes());
+            for &d in self.from[i].iter().chain(&self.to[i]) {
+                let d = u32::try_from(d).unwrap_or(UNREACHABLE);
+                bytes.extend(d.to_le_bytes());
+            }
+        }
+        std::fs::File::create(path)?.write_all(&bytes)
+    }
+
+    pub fn load(path: &str, map: &Map) -> Option<Self> {
+        let mut bytes = vec![];
+        std::fs::File::open(path)
+            .ok()?
+            .read_to_end(&mut bytes)
+            .ok()?;
+        if bytes.get(..4)? != MAGIC {
+            return None;
+        }
+        let saved = u64::from_le_bytes(bytes.get(4..12)?.try_into().ok()?);
+        let mut words = bytes[12..].chunks_exact(4).map(|chunk| {
+            match u32::from_le_bytes(chunk.try_into().unwrap()) {
+                UNREACHABLE => usize::MAX,
+                d => d as usize,
+            }
+        });
+        let dim = (words.next()?, words.next()?);
+        if saved != fingerprint(map) || dim != map.dim {
+            return None;
+        }
+        let size = dim.0 * dim.1;
+        let mut table = Self {
+            spots: vec![],
+            dim,
+            from: vec![],
+            to: vec![],
+            fingerprint: saved,
+        };
+        for _ in 0..words.next()? {
+            table.spots.push((words.next()?, words.next()?));
+            table.from.push(words.by_ref().take(size).collect());
+            table.to.push(words.by_ref().take(size).collect());
+            if table.to.last()?.len() != size {
+                return None;
+            }
+        }
+        Some(table)
+    }
+}
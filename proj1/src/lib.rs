@@ -0,0 +1,128 @@
+pub mod algorithms;
+pub mod anytime;
+pub mod bench;
+pub mod bidirectional;
+pub mod d_star_lite;
+pub mod deepening;
+pub mod export;
+pub mod fog;
+pub mod generator;
+pub mod hierarchical;
+pub mod jump_point;
+pub mod landmarks;
+pub mod map;
+pub mod multi_agent;
+pub mod search;
+pub mod space_time;
+pub mod stepper;
+pub mod trace;
+pub mod waypoints;
+
+use export::Recording;
+use map::Map;
+use std::{io::Write, time::Duration};
+use trace::Event;
+
+const SLEEPER_TIME: Duration = Duration::from_millis(0);
+
+pub struct Output {
+    writer: Box<dyn Write>,
+    pub echo: bool,   // also write everything to stdout
+    pub frames: bool, // write a map frame for every search step
+    pub delay: Duration,
+    pub trace: Option<Box<dyn Write>>, // where to write search events, if anywhere
+    pub recording: Option<Recording>,  // every frame, for exporting, if they're kept
+    pub snapshots: Option<Vec<Map>>,   // every frame's map, for stepping through afterward
+    nested: bool,                      // part of a bigger search, which reports the path
+}
+
+impl Output {
+    pub fn new(writer: Box<dyn Write>) -> Self {
+        Self {
+            writer,
+            echo: true,
+            frames: true,
+            delay: SLEEPER_TIME,
+            trace: None,
+            recording: None,
+            snapshots: None,
+            nested: false,
+        }
+    }
+
+    // Output that goes nowhere, for when only the outcome matters
+    pub fn silent() -> Self {
+        Self {
+            writer: Box::new(std::io::sink()),
+            echo: false,
+            frames: false,
+            delay: Duration::ZERO,
+            trace: None,
+            recording: None,
+            snapshots: None,
+            nested: false,
+        }
+    }
+
+    pub fn text(&mut self, string: &str) {
+        let bytes = string.as_bytes();
+        if self.echo {
+            std::io::stdout()
+                .write_all(bytes)
+                .expect("stdio write failed");
+        }
+        self.writer.write_all(bytes).expect("file write failed");
+        std::thread::sleep(self.delay);
+    }
+
+    pub fn frame(&mut self, map: &Map) {
+        if let Some(recording) = &mut self.recording {
+            recording.capture(map);
+        }
+        if let Some(snapshots) = &mut self.snapshots {
+            snapshots.push(map.clone());
+        }
+        if self.frames {
+            self.text(&map.map_text());
+        }
+    }
+
+    // Whether frames go anywhere, so searches that draw them specially can skip it
+    pub fn wants_frames(&self) -> bool {
+        self.frames || self.recording.is_some() || self.snapshots.is_some()
+    }
+
+    // Output for a search done as part of this one, which writes nothing but its
+    // frontier events to this output's trace. Give it back with `unnest` after.
+    pub fn nested(&mut self) -> Self {
+        let mut nested = Self::silent();
+        nested.trace = self.trace.take();
+        nested.nested = true;
+        nested
+    }
+
+    pub fn unnest(&mut self, nested: Self) {
+        self.trace = nested.trace;
+    }
+
+    pub fn event(&mut self, event: Event) {
+        if self.nested
+            && matches!(
+                event,
+                Event::Backtrack { .. } | Event::Path { .. } | Event::Fail
+            )
+        {
+            return;
+        }
+        if let Some(trace) = &mut self.trace {
+            writeln!(trace, "{}", event.json()).expect("trace write failed");
+        }
+    }
+
+    pub fn flush(&mut self) {
+        self.writer.flush().expect("Couldn't flush to file");
+        if let Some(trace) = &mut self.trace {
+            trace.flush().expect("Couldn't flush the trace");
+        }
+    }
+}
@@ -1,752 +1,513 @@
-use priority_queue::PriorityQueue;
+use proj1::{
+    algorithms::{self, Settings},
+    bench::{self, Format},
+    export::Recording,
+    generator::Generator,
+    map::{Edit, Map, TerrainTable, DEFAULT_CLIMB, DEFAULT_DIAGONAL},
+    search::Algorithm,
+    stepper::Stepper,
+    trace::Event,
+    Output,
+};
 use std::{
-    collections::VecDeque,
-    fmt::{Debug, Display},
     fs::File,
-    io::{BufRead, BufReader, Write},
-    vec,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    process::exit,
+    sync::Arc,
+    time::Duration,
 };
 
-const SLEEPER_TIME: std::time::Duration = std::time::Duration::from_millis(0);
-
-fn output(string: &str, file: &mut File) {
-    let bytes = string.as_bytes();
-    std::io::stdout().write(bytes).expect("stdio write failed");
-    file.write(bytes).expect("file write failed");
-    std::thread::sleep(SLEEPER_TIME);
-}
-
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
-enum Terrain {
-    Road,
-    Field,
-    Forest,
-    Hills,
-    River,
-    Mountians,
-    Water,
-}
-
-impl Terrain {
-    fn from(c: &char) -> Option<Self> {
-        match c {
-            'R' => Some(Self::Road),
-            'f' => Some(Self::Field),
-            'F' => Some(Self::Forest),
-            'h' => Some(Self::Hills),
-            'r' => Some(Self::River),
-            'M' => Some(Self::Mountians),
-            'W' => Some(Self::Water),
-            _ => None,
-        }
-    }
-    fn cost(&self) -> usize {
-        match self {
-            Self::Road => 1,
-            Self::Field => 2,
-            Self::Forest => 4,
-            Self::Hills => 5,
-            Self::River => 7,
-            Self::Mountians => 10,
-            Self::Water => usize::MAX, // shouldn't ever happen
-        }
-    }
-}
-
-#[derive(Copy, Clone, PartialEq, Eq)]
-enum Status {
-    Untraversed,
-    Path,
-    Up(bool),
-    Down(bool),
-    Left(bool),
-    Right(bool),
-}
-
-impl Status {
-    fn deactivate(&mut self) {
-        match self {
-            Status::Up(true) => *self = Status::Up(false),
-            Status::Down(true) => *self = Status::Down(false),
-            Status::Left(true) => *self = Status::Left(false),
-            Status::Right(true) => *self = Status::Right(false),
-            _ => (),
-        };
-    }
-}
-
-type Spot = (Terrain, Status);
-type Vec2 = (usize, usize);
-
-#[derive(Debug, Hash, PartialEq, Eq)]
-struct Visit {
-    step: usize,
-    loc: Vec2,
-    cost: usize,
-}
-
-impl Visit {
-    fn new(step: usize, loc: Vec2, cost: usize) -> Self {
-        Self { step, loc, cost }
-    }
+const USAGE: &str = "\
+Usage: proj1 [OPTIONS] [MAP]
+
+Arguments:
+  [MAP]  map file to search (default: data/map.txt)
+
+Options:
+  -a, --algs <LIST>  comma separated algorithms to run, or `all` (default: all)
+                     breadth_first, lowest_cost, greedy_best_first,
+                     a_star_taxicab (a_star_1), a_star_euclidean (a_star_2),
+                     a_star_octile (a_star_3), a_star_hex (a_star_4),
+                     a_star_alt (a_star_landmarks), iddfs, ida_star_taxicab (ida_star_1),
+                     ida_star_euclidean (ida_star_2), ida_star_octile (ida_star_3),
+                     ida_star_hex (ida_star_4), bidirectional_bfs,
+                     bidirectional_a_star_{taxicab,euclidean,octile,hex}
+                     (bidirectional_a_star_{1,2,3,4}), jump_point (jps),
+                     weighted_a_star, ara_star, d_star_lite, waypoints,
+                     cbs (conflict_based), space_time_a_star (space_time),
+                     hpa_star (hierarchical), lrta_star (fog)
+      --terrain <FILE>
+                     terrain definition file, one \"SYMBOL NAME COST\" per line
+                     (default: the standard terrain, see data/terrain.txt)
+  -8, --diagonals    allow diagonal moves (8-connected instead of 4-connected), on
+                     maps that aren't hex maps
+      --diagonal-cost <MULT>
+                     cost multiplier for diagonal moves, implies -8 (default: 1.414)
+      --climb-cost <N>
+                     extra cost for each level a move goes up, on maps with an
+                     elevation layer (default: 1)
+      --epsilon <E>  heuristic weight for weighted_a_star, and where ara_star
+                     starts (default: 2)
+      --epsilon-step <S>
+                     how much ara_star lowers epsilon each pass (default: 0.5)
+      --max-expansions <N>
+                     stop ara_star after expanding this many spots
+      --time-limit <MS>
+                     stop ara_star after this many milliseconds
+      --landmarks <N>
+                     how many landmarks a_star_alt uses (default: 8); the table
+                     is kept next to the map, with a .landmarks extension
+      --cluster-size <N>
+                     how many spots across hpa_star's clusters are (default: 16)
+      --sensor-radius <N>
+                     how many spots around it lrta_star's agent can see, in
+                     fog of war (default: 2)
+      --fog-cost <N> what lrta_star assumes spots it hasn't seen cost (default: 1)
+      --edits <FILE> edit script for d_star_lite to change the map with as it
+                     goes, one \"step N: set cell X,Y to terrain T\" per line
+      --generate <WxH>
+                     write a new random map of this size to MAP instead of
+                     searching one
+      --seed <N>     seed for --generate (default: 0)
+      --connected    make sure --generate's start and goal are connected
+      --bench <DIR>  run the algorithms on every map in DIR without writing results
+                     files, and print a row of stats for each run instead
+      --format <FMT> format for --bench rows, csv or json (default: csv)
+  -o, --out <DIR>    directory for the results files (default: results/<map name>)
+      --frames       write a map frame for every search step, even on a big map
+      --no-frames    don't write a map frame for every search step (the default for
+                     maps with more than 10000 spots)
+      --export <FMT> also write every search as an animated svg, or as an html page
+                     with a slider to step through it, to <DIR>/<alg>.<FMT>
+      --trace        also write every search's events (pops, pushes, the path and so
+                     on) to <DIR>/<alg>_trace.jsonl, one JSON object per line
+  -q, --quiet        don't echo the results to stdout
+  -i, --interactive  step through each search in the terminal once it's done, instead
+                     of echoing it (arrow keys step, space plays, q moves on)
+      --delay <MS>   pause after every write, in milliseconds (default: 0)
+  -h, --help         print this message
+";
+
+#[derive(Clone, Copy)]
+enum Export {
+    Svg,
+    Html,
 }
 
-#[derive(Clone)]
-struct Map {
-    map: Vec<Vec<Spot>>,
-    costs: Vec<Vec<usize>>,
-    display_costs: bool,
-    dim: Vec2,
-    start: Vec2,
-    goal: Vec2,
+// The most spots a map can have for its frames to be written without asking
+const FRAME_LIMIT: usize = 10_000;
+
+// How long each frame shows for in an exported svg
+const FRAME_TIME: Duration = Duration::from_millis(100);
+
+struct Args {
+    map: String,
+    algs: String,
+    terrain: Option<String>,
+    diagonals: Option<f64>,
+    climb: usize,
+    settings: Settings,
+    edits: Option<String>,
+    generate: Option<Generator>,
+    bench: Option<String>,
+    format: Format,
+    out: Option<String>,
+    frames: Option<bool>, // None to decide by the map's size
+    export: Option<Export>,
+    trace: bool,
+    echo: bool,
+    interactive: bool,
+    delay: Duration,
 }
 
-impl Map {
-    fn parse_line(reader: &mut BufReader<File>) -> Vec<String> {
-        let mut line = String::new();
-        reader.read_line(&mut line).expect("Error reading line");
-        line.trim().split(' ').map(|x| x.to_string()).collect()
-    }
-
-    fn from_file_path(path: &str) -> Self {
-        let file = File::open(path).expect("Couldn't open file");
-        let mut reader = BufReader::new(file);
-        let dim = Self::parse_line(&mut reader);
-        assert_eq!(
-            dim.len(),
-            2,
-            "Invalid number of arguments for map dimensions"
-        );
-        let dim: Vec2 = (
-            dim.get(0)
-                .expect("Couldn't read width")
-                .parse()
-                .expect("Couldn't parse width"),
-            dim.get(1)
-                .expect("Couldn't read height")
-                .parse()
-                .expect("Couldn't parse height"),
-        );
-        if dim.0 < 1 || dim.1 < 1 {
-            panic!("Dimensions are not large enough");
-        }
-
-        let start = Self::parse_line(&mut reader);
-        assert_eq!(
-            start.len(),
-            2,
-            "Invalid number of arguments for start position"
-        );
-        let start: Vec2 = (
-            start
-                .get(0)
-                .expect("Couldn't read start X")
-                .parse()
-                .expect("Couldn't parse start X"),
-            start
-                .get(1)
-                .expect("Couldn't read start Y")
-                .parse()
-                .expect("Couldn't parse start Y"),
-        );
-        if start.0 >= dim.0 || start.1 >= dim.1 {
-            panic!("Start position is out of bounds");
-        }
-
-        let goal = Self::parse_line(&mut reader);
-        assert_eq!(
-            goal.len(),
-            2,
-            "Invalid number of arguments for goal position"
-        );
-        let goal: Vec2 = (
-            goal.get(0)
-                .expect("Couldn't read goal X")
-                .parse()
-                .expect("Couldn't parse goal X"),
-            goal.get(1)
-                .expect("Couldn't read goal Y")
-                .parse()
-                .expect("Couldn't parse goal Y"),
-        );
-        if goal.0 >= dim.0 || goal.1 >= dim.1 {
-            panic!("Goal position is out of bounds");
-        }
-
-        let mut line_num = 0;
-        let mut map = Map {
-            map: vec![],
-            costs: vec![],
-            display_costs: false,
-            dim,
-            start,
-            goal,
+impl Args {
+    fn parse() -> Result<Self, String> {
+        let mut args = Args {
+            map: "data/map.txt".into(),
+            algs: "all".into(),
+            terrain: None,
+            diagonals: None,
+            climb: DEFAULT_CLIMB,
+            settings: Settings::default(),
+            edits: None,
+            generate: None,
+            bench: None,
+            format: Format::Csv,
+            out: None,
+            frames: None,
+            export: None,
+            trace: false,
+            echo: true,
+            interactive: false,
+            delay: Duration::ZERO,
         };
-        while line_num < dim.1 {
-            let mut line = String::new();
-            if reader.read_line(&mut line).expect("Error reading line") == 0 {
-                break;
-            }
-            let line = line.trim();
-            assert_eq!(line.len(), dim.0 as usize, "Map line is the wrong length");
-            let mut row: Vec<(Terrain, Status)> = vec![];
-            for c in line.chars() {
-                row.push((
-                    Terrain::from(&c).expect("Could not parse map character"),
-                    Status::Untraversed,
-                ));
-            }
-            map.map.push(row);
-            line_num += 1;
-        }
-        if line_num != dim.1 {
-            panic!("Not enough map data was provided");
-        }
-        map
-    }
-
-    fn map_text(&self) -> String {
-        let width = self.map.get(0).unwrap().len();
-        let divider = &format!(
-            "\n▐{}━━━▌\n▐",
-            String::from_iter(std::iter::repeat("━━━╋").take(width - 1))
-        );
-        let mut s = format!(
-            "▗{}▄▄▄▖\n▐",
-            String::from_iter(std::iter::repeat("▄▄▄▄").take(width - 1))
-        );
-
-        for (r, row) in self.map.iter().enumerate() {
-            let check_row = r == self.start.1 || r == self.goal.1;
-            for (c, tile) in row.iter().enumerate() {
-                let s_terrain = match tile.0 {
-                    Terrain::Road => "R",
-                    Terrain::Field => "f",
-                    Terrain::Forest => "F",
-                    Terrain::Hills => "h",
-                    Terrain::River => "r",
-                    Terrain::Mountians => "M",
-                    Terrain::Water => "W",
-                };
-                let s_status = match tile.1 {
-                    Status::Untraversed => " ",
-                    Status::Path => "█",        //
-                    Status::Up(true) => "▲",    // "⇑",
-                    Status::Down(true) => "▼",  // "⇓",
-                    Status::Left(true) => "◄",  // "«",
-                    Status::Right(true) => "►", // "»",
-                    Status::Up(false) => "↑",
-                    Status::Down(false) => "↓",
-                    Status::Left(false) => "←",
-                    Status::Right(false) => "→",
-                };
-                let s_start_goal = if check_row {
-                    if c == self.start.0 && r == self.start.1 {
-                        "S"
-                    } else if c == self.goal.0 && r == self.goal.1 {
-                        "G"
-                    } else {
-                        " "
+        let mut map = None;
+        let (mut size, mut seed, mut connected) = (None, 0, false);
+        let mut it = std::env::args().skip(1);
+        while let Some(arg) = it.next() {
+            let mut value = |name: &str| it.next().ok_or(format!("{name} needs a value"));
+            match arg.as_str() {
+                "-a" | "--algs" => args.algs = value(&arg)?,
+                "--terrain" => args.terrain = Some(value(&arg)?),
+                "-8" | "--diagonals" => {
+                    args.diagonals = args.diagonals.or(Some(DEFAULT_DIAGONAL));
+                }
+                "--diagonal-cost" => {
+                    let mult = value(&arg)?;
+                    let mult = mult
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|m| m.is_finite() && *m > 0.0)
+                        .ok_or(format!("Invalid diagonal cost {mult:?}"))?;
+                    args.diagonals = Some(mult);
+                }
+                "--climb-cost" => {
+                    let n = value(&arg)?;
+                    args.climb = n.parse().ok().ok_or(format!("Invalid climb cost {n:?}"))?;
+                }
+                "--epsilon" => {
+                    let e = value(&arg)?;
+                    args.settings.epsilon = e
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|e| e.is_finite() && *e >= 1.0)
+                        .ok_or(format!("Invalid epsilon {e:?}"))?;
+                }
+                "--epsilon-step" => {
+                    let step = value(&arg)?;
+                    args.settings.epsilon_step = step
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|s| s.is_finite() && *s > 0.0)
+                        .ok_or(format!("Invalid epsilon step {step:?}"))?;
+                }
+                "--max-expansions" => {
+                    let n = value(&arg)?;
+                    let n = n
+                        .parse()
+                        .map_err(|_| format!("Invalid expansion limit {n:?}"))?;
+                    args.settings.max_expansions = Some(n);
+                }
+                "--time-limit" => {
+                    let ms = value(&arg)?;
+                    let ms = ms
+                        .parse()
+                        .map_err(|_| format!("Invalid time limit {ms:?}"))?;
+                    args.settings.time_limit = Some(Duration::from_millis(ms));
+                }
+                "--landmarks" => {
+                    let n = value(&arg)?;
+                    args.settings.landmarks = n
+                        .parse()
+                        .ok()
+                        .filter(|&n| n >= 1)
+                        .ok_or(format!("Invalid landmark count {n:?}"))?;
+                }
+                "--cluster-size" => {
+                    let n = value(&arg)?;
+                    args.settings.cluster = n
+                        .parse()
+                        .ok()
+                        .filter(|&n| n >= 2)
+                        .ok_or(format!("Invalid cluster size {n:?}"))?;
+                }
+                "--sensor-radius" => {
+                    let n = value(&arg)?;
+                    args.settings.sensor_radius = n
+                        .parse()
+                        .ok()
+                        .filter(|&n| n >= 1)
+                        .ok_or(format!("Invalid sensor radius {n:?}"))?;
+                }
+                "--fog-cost" => {
+                    let n = value(&arg)?;
+                    args.settings.fog_cost = n
+                        .parse()
+                        .ok()
+                        .filter(|&n| n >= 1)
+                        .ok_or(format!("Invalid fog cost {n:?}"))?;
+                }
+                "--edits" => args.edits = Some(value(&arg)?),
+                "--generate" => {
+                    let dim = value(&arg)?;
+                    size = dim
+                        .split_once('x')
+                        .and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?)))
+                        .filter(|&(w, h)| w >= 1 && h >= 1)
+                        .map(Some)
+                        .ok_or(format!("Invalid map size {dim:?}"))?;
+                }
+                "--seed" => {
+                    let n = value(&arg)?;
+                    seed = n.parse().map_err(|_| format!("Invalid seed {n:?}"))?;
+                }
+                "--connected" => connected = true,
+                "--bench" => args.bench = Some(value(&arg)?),
+                "--format" => {
+                    args.format = match value(&arg)?.as_str() {
+                        "csv" => Format::Csv,
+                        "json" => Format::Json,
+                        other => return Err(format!("Unknown format {other:?}")),
                     }
-                } else {
-                    " "
-                };
-                if self.display_costs && self.costs[r][c] < 99 {
-                    let s_cost = &format!("{:0width$}", self.costs[r][c], width = 2);
-                    s += s_cost;
-                    if s_start_goal == "S" {
-                        s += "S";
-                    } else if s_start_goal == "G" && s_status == " " {
-                        s += "G";
-                    } else {
-                        s += s_status;
+                }
+                "-o" | "--out" => args.out = Some(value(&arg)?),
+                "--frames" => args.frames = Some(true),
+                "--no-frames" => args.frames = Some(false),
+                "--export" => {
+                    args.export = match value(&arg)?.as_str() {
+                        "svg" => Some(Export::Svg),
+                        "html" => Some(Export::Html),
+                        other => return Err(format!("Unknown export format {other:?}")),
                     }
-                } else {
-                    s += s_terrain;
-                    s += s_status;
-                    s += s_start_goal;
                 }
-                s += if c == row.len() - 1 { "▌" } else { "┃" }
-            }
-            if r != self.map.len() - 1 {
-                s += divider;
+                "--trace" => args.trace = true,
+                "-q" | "--quiet" => args.echo = false,
+                "-i" | "--interactive" => args.interactive = true,
+                "--delay" => {
+                    let ms = value(&arg)?;
+                    let ms = ms.parse().map_err(|_| format!("Invalid delay {ms:?}"))?;
+                    args.delay = Duration::from_millis(ms);
+                }
+                "-h" | "--help" => {
+                    print!("{USAGE}");
+                    exit(0);
+                }
+                _ if arg.starts_with('-') => return Err(format!("Unknown option {arg:?}")),
+                _ if map.is_none() => map = Some(arg),
+                _ => return Err(format!("Unexpected argument {arg:?}")),
             }
         }
-        s += &format!(
-            "\n▝{}▀▀▀▘\n",
-            String::from_iter(std::iter::repeat("▀▀▀▀").take(width - 1))
-        );
-        s
-    }
-
-    fn at(&self, loc: Vec2) -> Option<Spot> {
-        if loc.0 < self.dim.0 && loc.1 < self.dim.1 && self.map[loc.1][loc.0].0 != Terrain::Water {
-            return Some(self.map[loc.1][loc.0]);
-        }
-        None
-    }
-
-    fn at_mut(&mut self, loc: Vec2) -> Option<&mut Spot> {
-        if loc.0 < self.dim.0 && loc.1 < self.dim.1 {
-            return Some(&mut self.map[loc.1][loc.0]);
+        if let Some((width, height)) = size {
+            if map.is_none() {
+                return Err("--generate needs a MAP file to write to".into());
+            }
+            let mut generator = Generator::new(width, height, seed);
+            generator.connected = connected;
+            args.generate = Some(generator);
         }
-        None
-    }
-
-    fn follow(&self, loc: Vec2) -> Option<Vec2> {
-        match self.at(loc).expect("Followed path to invalid position") {
-            (_, Status::Up(_)) => Some((loc.0, loc.1 - 1)),
-            (_, Status::Down(_)) => Some((loc.0, loc.1 + 1)),
-            (_, Status::Left(_)) => Some((loc.0 - 1, loc.1)),
-            (_, Status::Right(_)) => Some((loc.0 + 1, loc.1)),
-            _ => None,
+        if let Some(map) = map {
+            args.map = map;
         }
+        Ok(args)
     }
 
-    fn backtrack(&mut self, f: &mut File) -> (usize, usize) {
-        let mut dist: usize = 0;
-        let mut cost: usize = 0;
-        let mut loc_opt = Some(self.goal);
-        loop {
-            match loc_opt {
-                Some(loc) => {
-                    loc_opt = self.follow(loc);
-                    self.at_mut(loc).unwrap().1 = Status::Path;
-                    dist += 1;
-                    cost += self.at(loc).unwrap().0.cost();
-                    if loc == self.start {
-                        self.display_costs = false;
-                    }
-                    output(&self.map_text(), f);
-                }
-                None => break,
-            }
+    fn parse_algs(&self, map: &Map) -> Result<Vec<Box<dyn Algorithm>>, String> {
+        if self.algs == "all" {
+            return Ok(algorithms::all(map, &self.settings));
         }
-        (dist, cost)
-    }
-
-    fn go_neighbors(&self, loc: &Vec2) -> Vec<(Option<Vec2>, Status)> {
-        vec![
-            (self.go_up(&loc), Status::Down(true)),
-            (self.go_down(&loc), Status::Up(true)),
-            (self.go_left(&loc), Status::Right(true)),
-            (self.go_right(&loc), Status::Left(true)),
-        ]
+        self.algs
+            .split(',')
+            .map(|name| {
+                algorithms::from_name(name.trim(), map, &self.settings)
+                    .ok_or(format!("Unknown algorithm {name:?}"))
+            })
+            .collect()
     }
+}
 
-    fn go_up(&self, loc: &Vec2) -> Option<Vec2> {
-        if loc.1 == 0 {
-            return None;
+fn main() {
+    let mut args = Args::parse().unwrap_or_else(|e| {
+        eprintln!("error: {e}\n\n{USAGE}");
+        exit(2);
+    });
+
+    if let Some(generator) = &args.generate {
+        let map = generator.generate();
+        if let Err(e) = std::fs::write(&args.map, map.file_text()) {
+            eprintln!("error: couldn't write {}: {e}", args.map);
+            exit(1);
         }
-        let loc = (loc.0, loc.1 - 1);
-        if let Some((_, s)) = self.at(loc) {
-            if s == Status::Untraversed {
-                return Some(loc);
-            }
+        if args.echo {
+            println!(
+                "Wrote a {}x{} map (seed {}) to {}:\n{:?}",
+                map.dim.0, map.dim.1, generator.seed, args.map, map
+            );
         }
-        None
+        return;
     }
 
-    fn go_down(&self, loc: &Vec2) -> Option<Vec2> {
-        let loc = (loc.0, loc.1 + 1);
-        if let Some((_, s)) = self.at(loc) {
-            if s == Status::Untraversed {
-                return Some(loc);
-            }
-        }
-        None
+    let terrains = match &args.terrain {
+        Some(path) => TerrainTable::from_file_path(path).unwrap_or_else(|e| {
+            eprintln!("error: failed to load terrain {path}: {e}");
+            exit(1);
+        }),
+        None => TerrainTable::default(),
+    };
+    let terrains = Arc::new(terrains);
+    if let Some(dir) = args.bench.clone() {
+        run_bench(args, &dir, terrains);
+        return;
     }
-
-    fn go_left(&self, loc: &Vec2) -> Option<Vec2> {
-        if loc.0 == 0 {
-            return None;
-        }
-        let loc = (loc.0 - 1, loc.1);
-        if let Some((_, s)) = self.at(loc) {
-            if s == Status::Untraversed {
-                return Some(loc);
-            }
-        }
-        None
+    let mut map = Map::from_file_path_with(&args.map, terrains).unwrap_or_else(|e| {
+        eprintln!("error: failed to load map {}: {e}", args.map);
+        exit(1);
+    });
+    if map.hex && args.diagonals.is_some() {
+        eprintln!(
+            "error: {} is a hex map, which has no diagonal moves",
+            args.map
+        );
+        exit(2);
     }
-
-    fn go_right(&self, loc: &Vec2) -> Option<Vec2> {
-        let loc = (loc.0 + 1, loc.1);
-        if let Some((_, s)) = self.at(loc) {
-            if s == Status::Untraversed {
-                return Some(loc);
-            }
+    map.diagonals = args.diagonals;
+    map.climb = args.climb;
+    if let Some(path) = &args.edits {
+        args.settings.edits =
+            Edit::script_from_file_path(path, &map.terrains).unwrap_or_else(|e| {
+                eprintln!("error: failed to load edits {path}: {e}");
+                exit(1);
+            });
+        if let Err(e) = check_edits(&map, &args.settings.edits) {
+            eprintln!("error: failed to load edits {path}: {e}");
+            exit(1);
         }
-        None
     }
-}
-
-impl Debug for Map {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let string = self.map_text();
-        write!(f, "{string}")
+    args.settings.landmark_file = Some(landmark_file(Path::new(&args.map)));
+    let algs = args.parse_algs(&map).unwrap_or_else(|e| {
+        eprintln!("error: {e}\n\n{USAGE}");
+        exit(2);
+    });
+    // drawing a big map on every step takes far longer than searching it
+    let small = map.dim.0 * map.dim.1 <= FRAME_LIMIT;
+    let frames = args.frames.unwrap_or(small);
+    if args.echo && small {
+        println!("The map data has been read successfully:\n{:?}", map);
+    } else if args.echo {
+        println!(
+            "The map data has been read successfully ({}x{})",
+            map.dim.0, map.dim.1
+        );
     }
-}
 
-impl Display for Map {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let string = self.map_text();
-        write!(f, "{string}")
+    // results for data/foo.txt go to results/foo/ unless told otherwise
+    let out_dir = args.out.unwrap_or_else(|| {
+        let stem = Path::new(&args.map).file_stem().unwrap_or_default();
+        format!("results/{}", stem.to_string_lossy())
+    });
+    if let Err(e) = std::fs::create_dir_all(&out_dir) {
+        eprintln!("error: couldn't create output directory {out_dir}: {e}");
+        exit(1);
     }
-}
 
-enum DistMode {
-    TaxiCab,
-    Euclidean,
-}
-
-fn dist(a: Vec2, b: Vec2, mode: DistMode) -> usize {
-    let d = (a.0.abs_diff(b.0), a.1.abs_diff(b.1));
-    match mode {
-        DistMode::TaxiCab => d.0 + d.1,
-        DistMode::Euclidean => ((d.0 * d.0 + d.1 * d.1) as f64).sqrt().floor() as usize,
-    }
-}
-
-// ---- THE ALGORITHMS ---- //
-fn breadth_first(map: &Map) {
-    // Variables
-    let mut f = File::create("results/breadth_first_results.txt").unwrap();
-    let mut done = false;
-    let mut map = map.clone();
-    let mut q = VecDeque::<(usize, Vec2)>::new();
-    let (start, goal) = (map.start, map.goal);
-    let mut step_prev = 1;
-    let mut pops = 0;
-
-    // Initialization
-    map.map[start.1][start.0].1 = Status::Path;
-    q.push_back((0, start));
-
-    // Loop
-    output("Running breadth first search\n", &mut f);
-    'main_loop: while let Some((step, loc)) = q.pop_front() {
-        pops += 1;
-        if step != step_prev {
-            output(&map.map_text(), &mut f);
+    for alg in algs {
+        let path = format!("{out_dir}/{}_results.txt", alg.name());
+        let file = File::create(&path).unwrap_or_else(|e| {
+            eprintln!("error: couldn't create {path}: {e}");
+            exit(1);
+        });
+        let mut out = Output::new(Box::new(file));
+        out.frames = frames;
+        out.echo = args.echo && !args.interactive;
+        out.delay = args.delay;
+        if args.interactive {
+            out.snapshots = Some(vec![]);
         }
-
-        map.at_mut(loc).unwrap().1.deactivate();
-
-        // For each untraversed valid neighbor, update its direction
-        for n in map.go_neighbors(&loc).into_iter() {
-            // (it came from here), and add it to the visit queue.
-            if let (Some(loc_new), dir) = n {
-                map.at_mut(loc_new).unwrap().1 = dir;
-                q.push_back((step + 1, loc_new));
-
-                if loc_new == goal {
-                    done = true;
-                    while let Some((_, loc)) = q.pop_front() {
-                        pops += 1;
-                        map.at_mut(loc).unwrap().1.deactivate();
-                    }
-                    output(&map.map_text(), &mut f);
-                    break 'main_loop;
-                }
-            }
+        if args.trace {
+            let path = format!("{out_dir}/{}_trace.jsonl", alg.name());
+            let file = File::create(&path).unwrap_or_else(|e| {
+                eprintln!("error: couldn't create {path}: {e}");
+                exit(1);
+            });
+            out.trace = Some(Box::new(BufWriter::new(file)));
+            out.event(Event::Start {
+                algorithm: alg.name(),
+                dim: map.dim,
+                start: map.start,
+                goal: map.goal,
+            });
         }
-
-        step_prev = step;
-    }
-
-    if done {
-        output("Doing backtracking\n", &mut f);
-        let (dist, cost) = map.backtrack(&mut f);
-        output(
-            &format!(
-                "Path found (dist: {dist} cost: {cost} iterations: {pops}) by breadth first alg\n"
-            ),
-            &mut f,
-        );
-    } else {
-        output(
-            "Breadth first search failed! No valid paths exist\n",
-            &mut f,
-        );
-    }
-    f.flush().expect("Couldn't flush to file");
-}
-
-fn lowest_cost_path(map: &Map) {
-    // Variables
-    let mut f = File::create("results/lowest_cost_results.txt").unwrap();
-    let mut done = false;
-    let mut map = map.clone();
-    let mut q = PriorityQueue::<Visit, usize>::new();
-    let (start, goal) = (map.start, map.goal);
-    let mut pops = 0;
-
-    // Initialization
-    map.costs = vec![vec![usize::MAX; map.dim.0]; map.dim.1];
-    map.display_costs = true;
-    map.map[start.1][start.0].1 = Status::Path;
-    map.costs[start.1][start.0] = map.map[start.1][start.0].0.cost();
-    q.push(Visit::new(0, start, map.costs[start.1][start.0]), 0);
-
-    // Loop
-    output("Running lowest cost search\n", &mut f);
-    'main_loop: while let Some((v, _)) = q.pop() {
-        pops += 1;
-        let (step, loc, cost) = (v.step, v.loc, v.cost);
-        output(&map.map_text(), &mut f);
-
-        map.at_mut(loc).unwrap().1.deactivate();
-
-        // For each valid unvisited neighbor, check if it would have been better to come from here.
-        // if so, update its cost and direction, then add it to the visit queue.
-        for n in map.go_neighbors(&loc).into_iter() {
-            if let (Some(loc_new), dir) = n {
-                let maybe_cost = cost + map.at(loc_new).unwrap().0.cost();
-                if maybe_cost < map.costs[loc_new.1][loc_new.0] {
-                    map.costs[loc_new.1][loc_new.0] = maybe_cost;
-                    map.at_mut(loc_new).unwrap().1 = dir;
-                    q.push(
-                        Visit::new(step + 1, loc_new, maybe_cost),
-                        usize::MAX - maybe_cost,
-                    );
-                }
-                if loc_new == goal {
-                    done = true;
-                    while let Some((v, _)) = q.pop() {
-                        pops += 1;
-                        map.at_mut(v.loc).unwrap().1.deactivate();
-                    }
-                    output(&map.map_text(), &mut f);
-                    break 'main_loop;
-                }
+        if args.export.is_some() {
+            out.recording = Some(Recording::new());
+        }
+        alg.run(&map, &mut out);
+        if let (Some(export), Some(recording)) = (args.export, out.recording) {
+            let (ext, text) = match export {
+                Export::Svg => ("svg", recording.svg(FRAME_TIME)),
+                Export::Html => ("html", recording.html(alg.name())),
+            };
+            let path = format!("{out_dir}/{}.{ext}", alg.name());
+            if let Err(e) = std::fs::write(&path, text) {
+                eprintln!("error: couldn't write {path}: {e}");
+                exit(1);
             }
         }
-    }
-
-    if done {
-        // Now do backtracking
-        output("Doing backtracking\n", &mut f);
-        let (dist, cost) = map.backtrack(&mut f);
-        output(
-            &format!(
-                "Path found (dist: {dist} cost: {cost} iterations: {pops}) by lowest cost alg\n"
-            ),
-            &mut f,
-        );
-    } else {
-        output("Lowest cost search failed! No valid paths exist\n", &mut f);
-    }
-    f.flush().expect("Couldn't flush to file");
-}
-
-fn greedy_best_first(map: &Map) {
-    // Variables
-    let mut f = File::create("results/greedy_best_first_results.txt").unwrap();
-    let mut done = false;
-    let mut map = map.clone();
-    let mut q = PriorityQueue::<Visit, usize>::new();
-    let (start, goal) = (map.start, map.goal);
-    let mut pops = 0;
-
-    // Initialization
-    map.costs = vec![vec![usize::MAX; map.dim.0]; map.dim.1];
-    map.display_costs = true;
-    map.map[start.1][start.0].1 = Status::Path;
-    map.costs[start.1][start.0] = map.map[start.1][start.0].0.cost();
-    q.push(Visit::new(0, start, map.costs[start.1][start.0]), 0);
-
-    // Loop
-    output("Running greedy best first search\n", &mut f);
-    'main_loop: while let Some((v, _)) = q.pop() {
-        pops += 1;
-        let (step, loc, cost) = (v.step, v.loc, v.cost);
-        output(&map.map_text(), &mut f);
-
-        map.at_mut(loc).unwrap().1.deactivate();
-
-        // For each untraversed valid neighbor, update its cost and direction,
-        // and add it to the priority queue (priority based on TaxiCab dist to goal).
-        for n in map.go_neighbors(&loc).into_iter() {
-            if let (Some(loc_new), dir) = n {
-                map.costs[loc_new.1][loc_new.0] = cost + map.at(loc_new).unwrap().0.cost();
-                map.at_mut(loc_new).unwrap().1 = dir;
-                q.push(
-                    Visit::new(step + 1, loc_new, map.costs[loc_new.1][loc_new.0]),
-                    usize::MAX - dist(loc_new, goal, DistMode::TaxiCab),
-                );
-                if loc_new == goal {
-                    done = true;
-                    while let Some((v, _)) = q.pop() {
-                        pops += 1;
-                        map.at_mut(v.loc).unwrap().1.deactivate();
-                    }
-                    output(&map.map_text(), &mut f);
-                    break 'main_loop;
-                }
+        if let Some(snapshots) = out.snapshots.take() {
+            if let Err(e) = Stepper::new(snapshots).run(alg.name()) {
+                eprintln!("error: couldn't step through {}: {e}", alg.name());
+                exit(1);
             }
         }
     }
-
-    if done {
-        // Now do backtracking
-        output("Doing backtracking\n", &mut f);
-        let (dist, cost) = map.backtrack(&mut f);
-        output(
-            &format!(
-            "Path found (dist: {dist} cost: {cost} iterations: {pops}) by greedy best first alg\n"
-        ),
-            &mut f,
-        );
-    } else {
-        output(
-            "Greedy best first search failed! No valid paths exist\n",
-            &mut f,
-        );
-    }
-    f.flush().expect("Couldn't flush to file");
 }
 
-fn a_star_taxicab(map: &Map) {
-    // Variables
-    let mut f = File::create("results/a_star_1_results.txt").unwrap();
-    let mut done = false;
-    let mut map = map.clone();
-    let mut q = PriorityQueue::<Visit, usize>::new();
-    let (start, goal) = (map.start, map.goal);
-    let mut pops = 0;
-
-    // Initialization
-    map.costs = vec![vec![usize::MAX; map.dim.0]; map.dim.1];
-    map.display_costs = true;
-    map.map[start.1][start.0].1 = Status::Path;
-    map.costs[start.1][start.0] = map.map[start.1][start.0].0.cost();
-    q.push(Visit::new(0, start, map.costs[start.1][start.0]), 0);
-
-    output("Running A* search (heuristic: taxicab dist)\n", &mut f);
-    'main_loop: while let Some((v, _)) = q.pop() {
-        pops += 1;
-        let (step, loc, cost) = (v.step, v.loc, v.cost);
-        output(&map.map_text(), &mut f);
-
-        map.at_mut(loc).unwrap().1.deactivate();
-
-        // For each valid unvisited neighbor, check if it would have been better to come from here.
-        // if so, update its cost and direction, the add it to the visit queue.
-        for n in map.go_neighbors(&loc).into_iter() {
-            if let (Some(loc_new), dir) = n {
-                let maybe_cost = cost + map.at(loc_new).unwrap().0.cost();
-                if maybe_cost < map.costs[loc_new.1][loc_new.0] {
-                    map.costs[loc_new.1][loc_new.0] = maybe_cost;
-                    map.at_mut(loc_new).unwrap().1 = dir;
-                    q.push(
-                        Visit::new(step + 1, loc_new, maybe_cost),
-                        usize::MAX - (maybe_cost + dist(loc_new, goal, DistMode::TaxiCab)),
-                    );
-                }
-                if loc_new == goal {
-                    done = true;
-                    while let Some((v, _)) = q.pop() {
-                        pops += 1;
-                        map.at_mut(v.loc).unwrap().1.deactivate();
-                    }
-                    output(&map.map_text(), &mut f);
-                    break 'main_loop;
-                }
-            }
-        }
-    }
+// A map's landmark table is kept next to it: data/foo.txt's is data/foo.landmarks
+fn landmark_file(map: &Path) -> String {
+    map.with_extension("landmarks").to_string_lossy().into()
+}
 
-    if done {
-        // Now do backtracking
-        output("Doing backtracking\n", &mut f);
-        let (dist, cost) = map.backtrack(&mut f);
-        output(
-            &format!(
-                "Path found (dist: {dist} cost: {cost} iterations: {pops}) by A* (taxicab) alg\n"
-            ),
-            &mut f,
-        );
-    } else {
-        output("A* search failed! No valid paths exist\n", &mut f);
+// Edits are read without a map to check them against, so make sure they all land on this one
+fn check_edits(map: &Map, edits: &[Edit]) -> Result<(), String> {
+    match edits
+        .iter()
+        .find(|e| e.loc.0 >= map.dim.0 || e.loc.1 >= map.dim.1)
+    {
+        Some(edit) => Err(format!("Cell {},{} is off the map", edit.loc.0, edit.loc.1)),
+        None => Ok(()),
     }
-    f.flush().expect("Couldn't flush to file");
 }
 
-fn a_star_euclidean(map: &Map) {
-    // Variables
-    let mut f = File::create("results/a_star_2_results.txt").unwrap();
-    let mut done = false;
-    let mut map = map.clone();
-    let mut q = PriorityQueue::<Visit, usize>::new();
-    let (start, goal) = (map.start, map.goal);
-    let mut pops = 0;
-
-    // Initialization
-    map.costs = vec![vec![usize::MAX; map.dim.0]; map.dim.1];
-    map.display_costs = true;
-    map.map[start.1][start.0].1 = Status::Path;
-    map.costs[start.1][start.0] = map.map[start.1][start.0].0.cost();
-    q.push(Visit::new(0, start, map.costs[start.1][start.0]), 0);
-
-    // Loop
-    output("Running A* search (heuristic: euclidean dist)\n", &mut f);
-    'main_loop: while let Some((v, _)) = q.pop() {
-        pops += 1;
-        let (step, loc, cost) = (v.step, v.loc, v.cost);
-        output(&map.map_text(), &mut f);
-
-        map.at_mut(loc).unwrap().1.deactivate();
+// Run the algorithms on every map in a directory, printing a row of stats for each run.
+// Anything in there that isn't a map, or that the edits don't fit, is skipped.
+fn run_bench(mut args: Args, dir: &str, terrains: Arc<TerrainTable>) {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)
+        .unwrap_or_else(|e| {
+            eprintln!("error: couldn't read {dir}: {e}");
+            exit(1);
+        })
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+        .collect();
+    paths.sort();
+    // only time what each algorithm does itself
+    args.settings.compare = false;
+    if let Some(path) = &args.edits {
+        args.settings.edits = Edit::script_from_file_path(path, &terrains).unwrap_or_else(|e| {
+            eprintln!("error: failed to load edits {path}: {e}");
+            exit(1);
+        });
+    }
 
-        // For each valid unvisited neighbor, check if it would have been better to come from here.
-        // if so, update its cost and direction, the add it to the visit queue.
-        for n in map.go_neighbors(&loc).into_iter() {
-            if let (Some(loc_new), dir) = n {
-                let maybe_cost = cost + map.at(loc_new).unwrap().0.cost();
-                if maybe_cost < map.costs[loc_new.1][loc_new.0] {
-                    map.costs[loc_new.1][loc_new.0] = maybe_cost;
-                    map.at_mut(loc_new).unwrap().1 = dir;
-                    q.push(
-                        Visit::new(step + 1, loc_new, maybe_cost),
-                        usize::MAX - (maybe_cost + dist(loc_new, goal, DistMode::Euclidean)),
-                    );
-                }
-                if loc_new == goal {
-                    done = true;
-                    while let Some((v, _)) = q.pop() {
-                        pops += 1;
-                        map.at_mut(v.loc).unwrap().1.deactivate();
-                    }
-                    output(&map.map_text(), &mut f);
-                    break 'main_loop;
-                }
+    let mut stdout = std::io::stdout();
+    print!("{}", bench::header(args.format));
+    let mut first = true;
+    for path in paths {
+        let mut map = match Map::from_file_path_with(&path.to_string_lossy(), terrains.clone()) {
+            Ok(map) => map,
+            Err(e) => {
+                eprintln!("skipping {}: {e}", path.display());
+                continue;
             }
+        };
+        if let Err(e) = check_edits(&map, &args.settings.edits) {
+            eprintln!("skipping {}: edits don't fit: {e}", path.display());
+            continue;
+        }
+        // hex maps have no diagonals to allow
+        map.diagonals = args.diagonals.filter(|_| !map.hex);
+        map.climb = args.climb;
+        args.settings.landmark_file = Some(landmark_file(&path));
+        let algs = args.parse_algs(&map).unwrap_or_else(|e| {
+            eprintln!("error: {e}\n\n{USAGE}");
+            exit(2);
+        });
+        let name = path.file_stem().unwrap_or_default().to_string_lossy();
+        for alg in algs {
+            let row = bench::measure(&name, &map, alg.as_ref());
+            print!("{}", bench::line(&row, args.format, first));
+            stdout.flush().expect("stdio write failed");
+            first = false;
         }
     }
-
-    if done {
-        // Now do backtracking
-        output("Doing backtracking\n", &mut f);
-        let (dist, cost) = map.backtrack(&mut f);
-        output(
-            &format!(
-                "Path found (dist: {dist} cost: {cost} iterations: {pops}) by A* (euclid) alg\n"
-            ),
-            &mut f,
-        );
-    } else {
-        output("A* search failed! No valid paths exist\n", &mut f);
-    }
-    f.flush().expect("Couldn't flush to file");
-}
-
-fn main() {
-    let map = Map::from_file_path("data/map.txt");
-    println!("The map data has been read successfully:\n{:?}", map);
-
-    breadth_first(&map);
-    lowest_cost_path(&map);
-    greedy_best_first(&map);
-    a_star_taxicab(&map);
-    a_star_euclidean(&map);
+    print!("{}", bench::footer(args.format));
 }
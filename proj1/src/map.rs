@@ -0,0 +1,1231 @@
+use crate::{trace::Event, Output};
+use std::{
+    fmt::{Debug, Display},
+    sync::Arc,
+};
+
+// A kind of ground, by the character maps use for it and what it costs to move onto
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Terrain {
+    symbol: char,
+    cost: Option<u32>, // None if it can't be crossed at all (kept small, for big maps)
+}
+
+impl Terrain {
+    pub const ROAD: Self = Self::new('R', Some(1));
+    pub const FIELD: Self = Self::new('f', Some(2));
+    pub const FOREST: Self = Self::new('F', Some(4));
+    pub const HILLS: Self = Self::new('h', Some(5));
+    pub const RIVER: Self = Self::new('r', Some(7));
+    pub const MOUNTAINS: Self = Self::new('M', Some(10));
+    pub const WATER: Self = Self::new('W', None);
+
+    pub const fn new(symbol: char, cost: Option<usize>) -> Self {
+        let cost = match cost {
+            Some(cost) => Some(cost as u32),
+            None => None,
+        };
+        Self { symbol, cost }
+    }
+
+    pub fn symbol(&self) -> char {
+        self.symbol
+    }
+
+    pub fn passable(&self) -> bool {
+        self.cost.is_some()
+    }
+
+    pub fn cost(&self) -> usize {
+        self.cost.map_or(usize::MAX, |cost| cost as usize) // shouldn't ever happen
+    }
+}
+
+// Every terrain a map can use, with their names
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TerrainTable {
+    kinds: Vec<(String, Terrain)>,
+}
+
+impl Default for TerrainTable {
+    fn default() -> Self {
+        let kinds = [
+            ("Road", Terrain::ROAD),
+            ("Field", Terrain::FIELD),
+            ("Forest", Terrain::FOREST),
+            ("Hills", Terrain::HILLS),
+            ("River", Terrain::RIVER),
+            ("Mountains", Terrain::MOUNTAINS),
+            ("Water", Terrain::WATER),
+        ];
+        Self {
+            kinds: kinds.map(|(name, t)| (name.to_string(), t)).to_vec(),
+        }
+    }
+}
+
+impl TerrainTable {
+    // Read a terrain definition file: one "SYMBOL NAME COST" per line, where COST is
+    // a whole number of at least 1, or "impassable". Blank lines and lines starting
+    // with # are skipped.
+    pub fn from_file_path(path: &str) -> Result<Self, String> {
+        let text =
+            std::fs::read_to_string(path).map_err(|e| format!("Couldn't open {path}: {e}"))?;
+        let mut table = Self { kinds: vec![] };
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let err = |what: &str| format!("Terrain line {}: {what}", i + 1);
+            let [symbol, name, cost] = line.split_whitespace().collect::<Vec<_>>()[..] else {
+                return Err(err("should look like \"SYMBOL NAME COST\""));
+            };
+            let mut chars = symbol.chars();
+            let (Some(symbol), None) = (chars.next(), chars.next()) else {
+                return Err(err(&format!("symbol {symbol:?} should be one character")));
+            };
+            let cost =
+                match cost {
+                    "impassable" => None,
+                    _ => Some(cost.parse::<u32>().ok().filter(|&c| c >= 1).ok_or(err(
+                        &format!("cost {cost:?} should be at least 1, or impassable"),
+                    ))?),
+                };
+            // names are checked against names only, since a one letter name would parse
+            // as a symbol
+            let named = |(n, _): &(String, Terrain)| n.eq_ignore_ascii_case(name);
+            if table.get(symbol).is_some() || table.kinds.iter().any(named) {
+                return Err(err(&format!("{symbol:?} or {name:?} is already defined")));
+            }
+            table.kinds.push((
+                name.to_string(),
+                Terrain::new(symbol, cost.map(|c| c as usize)),
+            ));
+        }
+        if table.kinds.is_empty() {
+            return Err("No terrain is defined".into());
+        }
+        Ok(table)
+    }
+
+    // The terrain a map character stands for
+    pub fn get(&self, symbol: char) -> Option<Terrain> {
+        self.kinds
+            .iter()
+            .find(|(_, t)| t.symbol == symbol)
+            .map(|&(_, t)| t)
+    }
+
+    // A terrain by its map character or its name
+    pub fn parse(&self, s: &str) -> Option<Terrain> {
+        let mut chars = s.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            return self.get(c);
+        }
+        self.kinds
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(s))
+            .map(|&(_, t)| t)
+    }
+
+    pub fn name(&self, terrain: Terrain) -> &str {
+        self.kinds
+            .iter()
+            .find(|&&(_, t)| t == terrain)
+            .map_or("Unknown", |(name, _)| name)
+    }
+}
+
+// What's wrong with a map file, and where: the line and column (counting from 1) and
+// the character there, when the problem is with one spot
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MapParseError {
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub character: Option<char>,
+    pub kind: MapErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MapErrorKind {
+    Io(String),                 // the file couldn't be read
+    Missing(String),            // the file ended before this header line
+    Header(String),             // a header line is malformed or out of bounds
+    WrongLength(usize, usize),  // a row has this many characters instead of the width
+    UnknownTerrain,             // the character isn't in the terrain table
+    UnknownHeight,              // the character in the elevation rows isn't a digit
+    MissingRows(usize),         // the file ended after this many rows
+    Impassable(String, String), // this start, goal or waypoint is on this terrain
+}
+
+impl MapParseError {
+    pub fn new(line: Option<usize>, kind: MapErrorKind) -> Self {
+        Self {
+            line,
+            column: None,
+            character: None,
+            kind,
+        }
+    }
+
+    fn at(self, column: usize, character: Option<char>) -> Self {
+        Self {
+            column: Some(column),
+            character,
+            ..self
+        }
+    }
+}
+
+impl Display for MapParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(line) = self.line {
+            write!(f, "line {line}")?;
+            if let Some(column) = self.column {
+                write!(f, ", column {column}")?;
+            }
+            write!(f, ": ")?;
+        }
+        match &self.kind {
+            MapErrorKind::Io(message) | MapErrorKind::Header(message) => write!(f, "{message}"),
+            MapErrorKind::Missing(what) => write!(f, "The file ends before the {what}"),
+            MapErrorKind::WrongLength(len, width) => {
+                write!(f, "Map line is {len} characters long instead of {width}")
+            }
+            MapErrorKind::UnknownTerrain => match self.character {
+                Some(c) => write!(f, "Could not parse map character {c:?}"),
+                None => write!(f, "Could not parse map character"),
+            },
+            MapErrorKind::UnknownHeight => match self.character {
+                Some(c) => write!(f, "Could not parse height {c:?} (should be 0 to 9)"),
+                None => write!(f, "Could not parse height"),
+            },
+            MapErrorKind::MissingRows(rows) => {
+                write!(f, "Not enough map data was provided ({rows} rows)")
+            }
+            MapErrorKind::Impassable(what, terrain) => {
+                write!(f, "The {what} is on {terrain}, which can't be crossed")
+            }
+        }
+    }
+}
+
+impl std::error::Error for MapParseError {}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Status {
+    Untraversed,
+    Path,
+    Up(bool),
+    Down(bool),
+    Left(bool),
+    Right(bool),
+    UpLeft(bool),
+    UpRight(bool),
+    DownLeft(bool),
+    DownRight(bool),
+    Agent(u16), // on the path of one of several agents
+    Hazard,     // where a patrol is
+}
+
+impl Status {
+    // Whether the spot is still on the frontier
+    pub fn is_active(&self) -> bool {
+        matches!(
+            self,
+            Status::Up(true)
+                | Status::Down(true)
+                | Status::Left(true)
+                | Status::Right(true)
+                | Status::UpLeft(true)
+                | Status::UpRight(true)
+                | Status::DownLeft(true)
+                | Status::DownRight(true)
+        )
+    }
+
+    pub fn deactivate(&mut self) {
+        match self {
+            Status::Up(true) => *self = Status::Up(false),
+            Status::Down(true) => *self = Status::Down(false),
+            Status::Left(true) => *self = Status::Left(false),
+            Status::Right(true) => *self = Status::Right(false),
+            Status::UpLeft(true) => *self = Status::UpLeft(false),
+            Status::UpRight(true) => *self = Status::UpRight(false),
+            Status::DownLeft(true) => *self = Status::DownLeft(false),
+            Status::DownRight(true) => *self = Status::DownRight(false),
+            _ => (),
+        };
+    }
+}
+
+// Every move as (dx, dy), with the back-pointer it leaves on the spot moved onto.
+// The first four are the straight moves, the rest are only used with diagonals on.
+const MOVES: [((isize, isize), Status); 8] = [
+    ((0, -1), Status::Down(true)),
+    ((0, 1), Status::Up(true)),
+    ((-1, 0), Status::Right(true)),
+    ((1, 0), Status::Left(true)),
+    ((-1, -1), Status::DownRight(true)),
+    ((1, -1), Status::DownLeft(true)),
+    ((-1, 1), Status::UpRight(true)),
+    ((1, 1), Status::UpLeft(true)),
+];
+
+// The same for hex maps, where odd rows sit half a spot to the right of even ones. Left
+// and right are the same for every row, but which spots are up and down a row depends
+// on whether the row is even or odd.
+const HEX_MOVES: [[((isize, isize), Status); 6]; 2] = [
+    [
+        ((-1, 0), Status::Right(true)),
+        ((1, 0), Status::Left(true)),
+        ((-1, -1), Status::DownRight(true)),
+        ((0, -1), Status::DownLeft(true)),
+        ((-1, 1), Status::UpRight(true)),
+        ((0, 1), Status::UpLeft(true)),
+    ],
+    [
+        ((-1, 0), Status::Right(true)),
+        ((1, 0), Status::Left(true)),
+        ((0, -1), Status::DownRight(true)),
+        ((1, -1), Status::DownLeft(true)),
+        ((0, 1), Status::UpRight(true)),
+        ((1, 1), Status::UpLeft(true)),
+    ],
+];
+
+const WAYPOINT_LABELS: [&str; 9] = ["1", "2", "3", "4", "5", "6", "7", "8", "9"];
+const AGENT_GLYPHS: [&str; 8] = ["●", "◆", "■", "★", "○", "◇", "□", "☆"];
+
+pub const DEFAULT_DIAGONAL: f64 = std::f64::consts::SQRT_2;
+pub const DEFAULT_CLIMB: usize = 1;
+
+pub type Spot = (Terrain, Status);
+pub type Vec2 = (usize, usize);
+
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Visit {
+    pub step: usize,
+    pub loc: Vec2,
+    pub cost: usize,
+}
+
+impl Visit {
+    pub fn new(step: usize, loc: Vec2, cost: usize) -> Self {
+        Self { step, loc, cost }
+    }
+}
+
+// Something on the map that moves or changes on a schedule known ahead of time
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Hazard {
+    // A guard on each of these spots in turn, a step on each, and then starting over.
+    // Nothing else can be on the guard's spot, or pass it going the other way.
+    Patrol(Vec<Vec2>),
+    // Spots that are `terrain` instead of their own from step `from` to step `to`, and
+    // again every `every` steps after that if it's given
+    Tide {
+        terrain: Terrain,
+        from: usize,
+        to: usize,
+        every: Option<usize>,
+        spots: Vec<Vec2>,
+    },
+}
+
+impl Hazard {
+    // Read a hazard: "patrol X,Y X,Y ..." or "tide T steps A-B every P: X,Y X,Y ...",
+    // where T is a map character or a terrain name, "-B" can be left off for a single
+    // step, and so can "every P" for a tide that only comes in once
+    fn parse(line: &str, terrains: &TerrainTable) -> Result<Self, String> {
+        let spots = |s: &str| {
+            s.split_whitespace()
+                .map(|pair| {
+                    let (x, y) = pair.split_once(',')?;
+                    Some((x.trim().parse().ok()?, y.trim().parse().ok()?))
+                })
+                .collect::<Option<Vec<Vec2>>>()
+                .filter(|spots| !spots.is_empty())
+                .ok_or(format!(
+                    "Couldn't parse the spots in {s:?} (\"X,Y X,Y ...\")"
+                ))
+        };
+        if let Some(rest) = line.trim().strip_prefix("patrol ") {
+            return Ok(Hazard::Patrol(spots(rest)?));
+        }
+        let tide = || -> Option<(Terrain, usize, usize, Option<usize>, &str)> {
+            let (terrain, rest) = line.trim().strip_prefix("tide ")?.split_once(" steps ")?;
+            let (when, rest) = rest.split_once(':')?;
+            let (steps, every) = match when.split_once(" every ") {
+                Some((steps, every)) => (steps, Some(every.trim().parse().ok()?)),
+                None => (when, None),
+            };
+            let (from, to) = steps.split_once('-').unwrap_or((steps, steps));
+            let (from, to) = (from.trim().parse().ok()?, to.trim().parse().ok()?);
+            Some((terrains.parse(terrain.trim())?, from, to, every, rest))
+        };
+        let Some((terrain, from, to, every, rest)) = tide() else {
+            return Err(format!(
+                "Couldn't parse hazard {:?} (\"patrol X,Y ...\" or \"tide T steps A-B every P: X,Y ...\")",
+                line.trim()
+            ));
+        };
+        if from > to || every.is_some_and(|every| to >= every) {
+            return Err(format!("Tide steps {from}-{to} don't fit in a cycle"));
+        }
+        Ok(Hazard::Tide {
+            terrain,
+            from,
+            to,
+            every,
+            spots: spots(rest)?,
+        })
+    }
+
+    pub fn spots(&self) -> &[Vec2] {
+        match self {
+            Hazard::Patrol(spots) | Hazard::Tide { spots, .. } => spots,
+        }
+    }
+
+    // Where a patrol is at step `t`
+    pub fn patrol_at(&self, t: usize) -> Option<Vec2> {
+        match self {
+            Hazard::Patrol(spots) => Some(spots[t % spots.len()]),
+            Hazard::Tide { .. } => None,
+        }
+    }
+
+    // Whether a tide is in at step `t`
+    pub fn tide_in(&self, t: usize) -> bool {
+        match *self {
+            Hazard::Tide {
+                from, to, every, ..
+            } => (from..=to).contains(&every.map_or(t, |every| t % every)),
+            Hazard::Patrol(_) => false,
+        }
+    }
+}
+
+impl Display for Hazard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let spots: Vec<String> = self
+            .spots()
+            .iter()
+            .map(|(x, y)| format!("{x},{y}"))
+            .collect();
+        match self {
+            Hazard::Patrol(_) => write!(f, "patrol {}", spots.join(" ")),
+            Hazard::Tide {
+                terrain,
+                from,
+                to,
+                every,
+                ..
+            } => {
+                write!(f, "tide {} steps {from}-{to}", terrain.symbol())?;
+                if let Some(every) = every {
+                    write!(f, " every {every}")?;
+                }
+                write!(f, ": {}", spots.join(" "))
+            }
+        }
+    }
+}
+
+// A change to the map partway through a run: at `step`, the spot at `loc` becomes `terrain`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Edit {
+    pub step: usize,
+    pub loc: Vec2,
+    pub terrain: Terrain,
+}
+
+impl Edit {
+    // Read an edit script: one "step N: set cell X,Y to terrain T" per line, where T is
+    // a map character or a terrain name. Blank lines and lines starting with # are skipped.
+    pub fn script_from_file_path(path: &str, terrains: &TerrainTable) -> Result<Vec<Self>, String> {
+        let text =
+            std::fs::read_to_string(path).map_err(|e| format!("Couldn't open {path}: {e}"))?;
+        let mut edits = vec![];
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let edit = Self::parse(line, terrains).ok_or(format!(
+                "Edit line {} should look like \"step N: set cell X,Y to terrain T\"",
+                i + 1
+            ))?;
+            edits.push(edit);
+        }
+        Ok(edits)
+    }
+
+    fn parse(line: &str, terrains: &TerrainTable) -> Option<Self> {
+        let (step, rest) = line.strip_prefix("step ")?.split_once(':')?;
+        let (loc, terrain) = rest
+            .trim()
+            .strip_prefix("set cell ")?
+            .split_once(" to terrain ")?;
+        let (x, y) = loc.split_once(',')?;
+        Some(Self {
+            step: step.trim().parse().ok()?,
+            loc: (x.trim().parse().ok()?, y.trim().parse().ok()?),
+            terrain: terrains.parse(terrain.trim())?,
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct Map {
+    pub map: Vec<Spot>,    // one spot after another, a row at a time (see `index`)
+    pub costs: Vec<usize>, // laid out the same way, when a search keeps them
+    pub display_costs: bool,
+    pub dim: Vec2,
+    pub start: Vec2,
+    pub goal: Vec2,
+    pub waypoints: Vec<Vec2>, // spots to visit between the start and the goal, if any
+    pub agents: Vec<(Vec2, Vec2)>, // (start, goal) of any agents besides the main one
+    pub hazards: Vec<Hazard>, // patrols and tides, for the searches that plan around them
+    pub diagonals: Option<f64>, // cost multiplier for diagonal moves, if they're allowed
+    pub hex: bool,            // six neighbors a spot instead of four or eight (see HEX_MOVES)
+    pub elevation: Vec<u8>,   // the height of each spot, laid out like `map` (empty if flat)
+    pub climb: usize,         // extra cost for each level a move goes up
+    pub terrains: Arc<TerrainTable>,
+}
+
+impl Map {
+    // The next line of the file split into words, with its line number
+    fn parse_line<'a>(
+        lines: &mut impl Iterator<Item = (usize, &'a str)>,
+        what: &str,
+    ) -> Result<(usize, Vec<&'a str>), MapParseError> {
+        let (num, line) = lines
+            .next()
+            .ok_or_else(|| MapParseError::new(None, MapErrorKind::Missing(what.into())))?;
+        Ok((num, line.split_whitespace().collect()))
+    }
+
+    fn parse_pair<'a>(
+        lines: &mut impl Iterator<Item = (usize, &'a str)>,
+        what: &str,
+        names: (&str, &str),
+    ) -> Result<(usize, Vec2), MapParseError> {
+        let (num, line) = Self::parse_line(lines, what)?;
+        let err = |message: String| MapParseError::new(Some(num), MapErrorKind::Header(message));
+        if line.len() != 2 {
+            return Err(err(format!("Invalid number of arguments for {what}")));
+        }
+        let parse = |s: &str, name: &str| {
+            s.parse::<usize>()
+                .map_err(|_| err(format!("Couldn't parse {name} ({s:?})")))
+        };
+        Ok((num, (parse(line[0], names.0)?, parse(line[1], names.1)?)))
+    }
+
+    // `dim.1` rows of `dim.0` characters each. The line number and indent of each row
+    // go in `where_rows`, for errors.
+    fn parse_rows<'a, T>(
+        rows: &mut impl Iterator<Item = (usize, &'a str)>,
+        dim: Vec2,
+        parse: impl Fn(char) -> Option<T>,
+        unknown: MapErrorKind,
+        where_rows: &mut Vec<(usize, usize)>,
+    ) -> Result<Vec<T>, MapParseError> {
+        // not reserved up front, so a made-up size fails on the rows instead of the memory
+        let mut spots = vec![];
+        while where_rows.len() < dim.1 {
+            let Some((num, line)) = rows.next() else {
+                let found = where_rows.len();
+                return Err(MapParseError::new(None, MapErrorKind::MissingRows(found)));
+            };
+            let indent = line.chars().take_while(|c| c.is_whitespace()).count();
+            let line = line.trim();
+            let mut len = 0;
+            for (i, c) in line.chars().enumerate() {
+                let err = |kind| MapParseError::new(Some(num), kind).at(indent + i + 1, Some(c));
+                if i == dim.0 {
+                    return Err(err(MapErrorKind::WrongLength(line.chars().count(), dim.0)));
+                }
+                spots.push(parse(c).ok_or_else(|| err(unknown.clone()))?);
+                len += 1;
+            }
+            if len != dim.0 {
+                return Err(
+                    MapParseError::new(Some(num), MapErrorKind::WrongLength(len, dim.0))
+                        .at(indent + len + 1, None),
+                );
+            }
+            where_rows.push((num, indent));
+        }
+        Ok(spots)
+    }
+
+    pub fn from_file_path(path: &str) -> Result<Self, MapParseError> {
+        Self::from_file_path_with(path, Arc::default())
+    }
+
+    // Read a map that's made of the given terrain
+    pub fn from_file_path_with(
+        path: &str,
+        terrains: Arc<TerrainTable>,
+    ) -> Result<Self, MapParseError> {
+        let text = std::fs::read_to_string(path).map_err(|e| {
+            MapParseError::new(None, MapErrorKind::Io(format!("Couldn't open {path}: {e}")))
+        })?;
+        Self::from_text_with(&text, terrains)
+    }
+
+    // The same for a map that's already been read in
+    pub fn from_text(text: &str) -> Result<Self, MapParseError> {
+        Self::from_text_with(text, Arc::default())
+    }
+
+    pub fn from_text_with(text: &str, terrains: Arc<TerrainTable>) -> Result<Self, MapParseError> {
+        let mut lines = text.lines().enumerate().map(|(i, line)| (i + 1, line));
+        let header = |num: usize, message: &str| {
+            MapParseError::new(Some(num), MapErrorKind::Header(message.into()))
+        };
+
+        let (num, dim) = Self::parse_pair(&mut lines, "map dimensions", ("width", "height"))?;
+        if dim.0 < 1 || dim.1 < 1 {
+            return Err(header(num, "Dimensions are not large enough"));
+        }
+        if dim.0.checked_mul(dim.1).is_none() {
+            return Err(header(num, "Dimensions are too large"));
+        }
+        let in_bounds = |loc: Vec2| loc.0 < dim.0 && loc.1 < dim.1;
+
+        let (num, start) = Self::parse_pair(&mut lines, "start position", ("start X", "start Y"))?;
+        if !in_bounds(start) {
+            return Err(header(num, "Start position is out of bounds"));
+        }
+
+        let (num, goal) = Self::parse_pair(&mut lines, "goal position", ("goal X", "goal Y"))?;
+        if !in_bounds(goal) {
+            return Err(header(num, "Goal position is out of bounds"));
+        }
+
+        // then optionally "hex" for a hex map, "waypoints N" and a line with the X Y of
+        // each of them, "agents N" and a line with the start X Y and goal X Y of each
+        // extra agent, and "hazards N" and a line for each hazard (see Hazard::parse)
+        let mut hex = false;
+        let mut waypoints = vec![];
+        let mut agents = vec![];
+        let mut hazards = vec![];
+        let mut first_row = None;
+        while let Some((num, line)) = lines.next() {
+            let count = |count: &str, what: &str| {
+                let count = count.trim();
+                count
+                    .parse::<usize>()
+                    .map_err(|_| header(num, &format!("Couldn't parse {what} count ({count:?})")))
+            };
+            if line.trim() == "hex" {
+                hex = true;
+            } else if let Some(n) = line.trim().strip_prefix("waypoints") {
+                for i in 0..count(n, "waypoint")? {
+                    let (num, loc) =
+                        Self::parse_pair(&mut lines, "waypoint", ("waypoint X", "waypoint Y"))?;
+                    if !in_bounds(loc) {
+                        return Err(header(num, &format!("Waypoint {} is out of bounds", i + 1)));
+                    }
+                    waypoints.push(loc);
+                }
+            } else if let Some(n) = line.trim().strip_prefix("agents") {
+                for i in 0..count(n, "agent")? {
+                    let (num, line) = Self::parse_line(&mut lines, "agent")?;
+                    let nums: Vec<usize> = line.iter().filter_map(|s| s.parse().ok()).collect();
+                    let [sx, sy, gx, gy] = nums[..] else {
+                        let message = format!("Couldn't parse agent {} (\"SX SY GX GY\")", i + 1);
+                        return Err(header(num, &message));
+                    };
+                    if !in_bounds((sx, sy)) || !in_bounds((gx, gy)) {
+                        return Err(header(num, &format!("Agent {} is out of bounds", i + 1)));
+                    }
+                    agents.push(((sx, sy), (gx, gy)));
+                }
+            } else if let Some(n) = line.trim().strip_prefix("hazards") {
+                for i in 0..count(n, "hazard")? {
+                    let (num, line) = lines.next().ok_or_else(|| {
+                        MapParseError::new(None, MapErrorKind::Missing("hazard".into()))
+                    })?;
+                    let hazard = Hazard::parse(line, &terrains).map_err(|e| header(num, &e))?;
+                    if !hazard.spots().iter().all(|&loc| in_bounds(loc)) {
+                        return Err(header(num, &format!("Hazard {} is out of bounds", i + 1)));
+                    }
+                    hazards.push(hazard);
+                }
+            } else {
+                first_row = Some((num, line));
+                break;
+            }
+        }
+
+        let mut map = Map {
+            map: vec![],
+            costs: vec![],
+            display_costs: false,
+            dim,
+            start,
+            goal,
+            waypoints,
+            agents,
+            hazards,
+            diagonals: None,
+            hex,
+            elevation: vec![],
+            climb: DEFAULT_CLIMB,
+            terrains,
+        };
+        let mut rows = first_row.into_iter().chain(lines);
+        let terrain = |c| map.terrains.get(c).map(|t| (t, Status::Untraversed));
+        let unknown = MapErrorKind::UnknownTerrain;
+        let mut where_rows = vec![];
+        map.map = Self::parse_rows(&mut rows, dim, terrain, unknown, &mut where_rows)?;
+
+        // then optionally "elevation" and the height of every spot from 0 to 9, laid out
+        // like the terrain
+        if rows
+            .find(|(_, line)| !line.trim().is_empty())
+            .map(|(_, line)| line.trim())
+            == Some("elevation")
+        {
+            let height = |c: char| c.to_digit(10).map(|h| h as u8);
+            let unknown = MapErrorKind::UnknownHeight;
+            map.elevation = Self::parse_rows(&mut rows, dim, height, unknown, &mut vec![])?;
+        }
+
+        // Everything the searches start from or have to reach has to be somewhere they can be
+        let mut stops = vec![
+            (map.start, "start".to_string()),
+            (map.goal, "goal".to_string()),
+        ];
+        for (i, &loc) in map.waypoints.iter().enumerate() {
+            stops.push((loc, format!("waypoint {}", i + 1)));
+        }
+        for (i, &(start, goal)) in map.agents.iter().enumerate() {
+            stops.push((start, format!("start of agent {}", i + 1)));
+            stops.push((goal, format!("goal of agent {}", i + 1)));
+        }
+        for (loc, what) in stops {
+            let terrain = map.map[map.index(loc)].0;
+            if !terrain.passable() {
+                let (num, indent) = where_rows[loc.1];
+                let name = map.terrains.name(terrain).to_string();
+                return Err(
+                    MapParseError::new(Some(num), MapErrorKind::Impassable(what, name))
+                        .at(indent + loc.0 + 1, Some(terrain.symbol())),
+                );
+            }
+        }
+        Ok(map)
+    }
+
+    // A map of the given rows of terrain, with nothing else on it
+    pub fn new(
+        rows: Vec<Vec<Terrain>>,
+        start: Vec2,
+        goal: Vec2,
+        terrains: Arc<TerrainTable>,
+    ) -> Self {
+        let dim = (rows.first().map_or(0, |row| row.len()), rows.len());
+        Self {
+            map: rows
+                .into_iter()
+                .flatten()
+                .map(|t| (t, Status::Untraversed))
+                .collect(),
+            costs: vec![],
+            display_costs: false,
+            dim,
+            start,
+            goal,
+            waypoints: vec![],
+            agents: vec![],
+            hazards: vec![],
+            diagonals: None,
+            hex: false,
+            elevation: vec![],
+            climb: DEFAULT_CLIMB,
+            terrains,
+        }
+    }
+
+    // The `dim` spots from `origin` on as a map of their own, moving the same way and
+    // costing the same to move around in. On a hex map `origin` has to be on an even
+    // row, or the rows would shift the other way.
+    pub fn crop(&self, origin: Vec2, dim: Vec2) -> Self {
+        let rows = (origin.1..origin.1 + dim.1)
+            .map(|y| {
+                (origin.0..origin.0 + dim.0)
+                    .map(|x| self.map[self.index((x, y))].0)
+                    .collect()
+            })
+            .collect();
+        let mut map = Self::new(rows, (0, 0), (0, 0), self.terrains.clone());
+        map.diagonals = self.diagonals;
+        map.hex = self.hex;
+        map.climb = self.climb;
+        if !self.elevation.is_empty() {
+            map.elevation = (origin.1..origin.1 + dim.1)
+                .flat_map(|y| (origin.0..origin.0 + dim.0).map(move |x| (x, y)))
+                .map(|loc| self.elevation[self.index(loc)])
+                .collect();
+        }
+        map
+    }
+
+    // The map in the format `from_file_path` reads
+    pub fn file_text(&self) -> String {
+        let mut s = format!(
+            "{} {}\n{} {}\n{} {}\n",
+            self.dim.0, self.dim.1, self.start.0, self.start.1, self.goal.0, self.goal.1
+        );
+        if self.hex {
+            s += "hex\n";
+        }
+        if !self.waypoints.is_empty() {
+            s += &format!("waypoints {}\n", self.waypoints.len());
+            for &(x, y) in &self.waypoints {
+                s += &format!("{x} {y}\n");
+            }
+        }
+        if !self.agents.is_empty() {
+            s += &format!("agents {}\n", self.agents.len());
+            for &((sx, sy), (gx, gy)) in &self.agents {
+                s += &format!("{sx} {sy} {gx} {gy}\n");
+            }
+        }
+        if !self.hazards.is_empty() {
+            s += &format!("hazards {}\n", self.hazards.len());
+            for hazard in &self.hazards {
+                s += &format!("{hazard}\n");
+            }
+        }
+        for row in self.rows() {
+            s.extend(row.iter().map(|spot| spot.0.symbol()));
+            s.push('\n');
+        }
+        if !self.elevation.is_empty() {
+            s += "elevation\n";
+            for row in self.elevation.chunks(self.dim.0.max(1)) {
+                s.extend(row.iter().map(|&h| (b'0' + h) as char));
+                s.push('\n');
+            }
+        }
+        s
+    }
+
+    pub fn map_text(&self) -> String {
+        if self.hex {
+            return self.hex_text();
+        }
+        let width = self.dim.0;
+        let divider = &format!("\n▐{}━━━▌\n▐", "━━━╋".repeat(width - 1));
+        let mut s = format!("▗{}▄▄▄▖\n▐", "▄▄▄▄".repeat(width - 1));
+
+        for (r, row) in self.rows().enumerate() {
+            for c in 0..row.len() {
+                s += &self.spot_text((c, r));
+                s += if c == row.len() - 1 { "▌" } else { "┃" }
+            }
+            if r != self.dim.1 - 1 {
+                s += divider;
+            }
+        }
+        s += &format!("\n▝{}▀▀▀▘\n", "▀▀▀▀".repeat(width - 1));
+        s
+    }
+
+    // Hex maps are drawn as pointy topped hexes, with every odd row shifted half a spot
+    // to the right:
+    //  ╱ ╲ ╱ ╲
+    // │f S│M  │
+    //  ╲ ╱ ╲ ╱ ╲
+    //   │f  │F G│
+    //    ╲ ╱ ╲ ╱
+    fn hex_text(&self) -> String {
+        // the edges between two rows, or above the first or below the last: a row's
+        // hexes have a ╱ and a ╲ over them, and a ╲ and a ╱ under them
+        let edges = |above: Option<usize>, below: Option<usize>| {
+            let mut line = vec![' '; 4 * self.dim.0 + 2];
+            for c in 0..self.dim.0 {
+                if let Some(r) = above {
+                    line[4 * c + 1 + 2 * (r % 2)] = '╲';
+                    line[4 * c + 3 + 2 * (r % 2)] = '╱';
+                }
+                if let Some(r) = below {
+                    line[4 * c + 1 + 2 * (r % 2)] = '╱';
+                    line[4 * c + 3 + 2 * (r % 2)] = '╲';
+                }
+            }
+            line.into_iter().collect::<String>().trim_end().to_string() + "\n"
+        };
+
+        let mut s = edges(None, Some(0));
+        for r in 0..self.dim.1 {
+            s += if r % 2 == 1 { "  │" } else { "│" };
+            for c in 0..self.dim.0 {
+                s += &self.spot_text((c, r));
+                s += "│";
+            }
+            s += "\n";
+            s += &edges(Some(r), (r + 1 < self.dim.1).then_some(r + 1));
+        }
+        s
+    }
+
+    // What's drawn in a spot: its terrain (or its cost so far), which way it was reached
+    // from, and whether anything starts or ends there
+    fn spot_text(&self, (c, r): Vec2) -> String {
+        let tile = self.map[self.index((c, r))];
+        let s_terrain = tile.0.symbol();
+        let s_status = match tile.1 {
+            Status::Untraversed => " ",
+            Status::Path => "█",        //
+            Status::Up(true) => "▲",    // "⇑",
+            Status::Down(true) => "▼",  // "⇓",
+            Status::Left(true) => "◄",  // "«",
+            Status::Right(true) => "►", // "»",
+            Status::Up(false) => "↑",
+            Status::Down(false) => "↓",
+            Status::Left(false) => "←",
+            Status::Right(false) => "→",
+            Status::UpLeft(true) => "◤",
+            Status::UpRight(true) => "◥",
+            Status::DownLeft(true) => "◣",
+            Status::DownRight(true) => "◢",
+            Status::UpLeft(false) => "↖",
+            Status::UpRight(false) => "↗",
+            Status::DownLeft(false) => "↙",
+            Status::DownRight(false) => "↘",
+            Status::Agent(i) => AGENT_GLYPHS.get(i as usize).unwrap_or(&"*"),
+            Status::Hazard => "×",
+        };
+        let s_start_goal = if c == self.start.0 && r == self.start.1 {
+            "S"
+        } else if c == self.goal.0 && r == self.goal.1 {
+            "G"
+        } else {
+            " "
+        };
+        // waypoints are numbered in the order the map lists them, and the extra
+        // agents' starts and goals are lettered (B and b for the first one)
+        let s_start_goal = match self.waypoints.iter().position(|&w| w == (c, r)) {
+            Some(i) if s_start_goal == " " => WAYPOINT_LABELS.get(i).unwrap_or(&"+"),
+            _ => s_start_goal,
+        };
+        let mut agent_label = String::new();
+        if s_start_goal == " " {
+            for (i, &(start, goal)) in self.agents.iter().enumerate().take(25) {
+                let letter = (b'B' + i as u8) as char;
+                if start == (c, r) {
+                    agent_label = letter.to_string();
+                } else if goal == (c, r) {
+                    agent_label = letter.to_ascii_lowercase().to_string();
+                }
+            }
+        }
+        let s_start_goal = if agent_label.is_empty() {
+            s_start_goal
+        } else {
+            &agent_label
+        };
+        let mut s = String::new();
+        let cost = self.costs.get(self.index((c, r))).copied();
+        if self.display_costs && cost.is_some_and(|cost| cost < 99) {
+            let s_cost = &format!("{:0width$}", cost.unwrap(), width = 2);
+            s += s_cost;
+            if s_start_goal == "S" {
+                s += "S";
+            } else if s_start_goal == "G" && s_status == " " {
+                s += "G";
+            } else {
+                s += s_status;
+            }
+        } else {
+            s.push(s_terrain);
+            s += s_status;
+            s += s_start_goal;
+        }
+        s
+    }
+
+    pub fn at(&self, loc: Vec2) -> Option<Spot> {
+        if loc.0 < self.dim.0 && loc.1 < self.dim.1 && self.map[self.index(loc)].0.passable() {
+            return Some(self.map[self.index(loc)]);
+        }
+        None
+    }
+
+    // The spots a row at a time
+    pub fn rows(&self) -> std::slice::Chunks<'_, Spot> {
+        self.map.chunks(self.dim.0.max(1))
+    }
+
+    // Where a spot lives in a flat, row-major array with one entry per spot
+    pub fn index(&self, loc: Vec2) -> usize {
+        loc.1 * self.dim.0 + loc.0
+    }
+
+    pub fn at_mut(&mut self, loc: Vec2) -> Option<&mut Spot> {
+        if loc.0 < self.dim.0 && loc.1 < self.dim.1 {
+            let i = self.index(loc);
+            return Some(&mut self.map[i]);
+        }
+        None
+    }
+
+    pub fn follow(&self, loc: Vec2) -> Option<Vec2> {
+        let (dx, dy) = match self.at(loc).expect("Followed path to invalid position") {
+            (_, Status::Up(_)) => (0, -1),
+            (_, Status::Down(_)) => (0, 1),
+            (_, Status::Left(_)) => (-1, 0),
+            (_, Status::Right(_)) => (1, 0),
+            (_, Status::UpLeft(_)) => (-1, -1),
+            (_, Status::UpRight(_)) => (1, -1),
+            (_, Status::DownLeft(_)) => (-1, 1),
+            (_, Status::DownRight(_)) => (1, 1),
+            _ => return None,
+        };
+        // on hex maps the rows above and below are half a spot over, so going up or down
+        // to the left or right only changes x for every other row
+        let dx = match self.hex && dy != 0 {
+            true => (dx - 1) / 2 + (loc.1 % 2) as isize,
+            false => dx,
+        };
+        Some((loc.0.wrapping_add_signed(dx), loc.1.wrapping_add_signed(dy)))
+    }
+
+    // Follow the back-pointers from the goal to the start, marking the path on the way.
+    // Returns the path (start first) and its cost.
+    pub fn backtrack(&mut self, out: &mut Output) -> (Vec<Vec2>, usize) {
+        let mut path = vec![];
+        let mut cost: usize = 0;
+        let mut loc_opt = Some(self.goal);
+        while let Some(loc) = loc_opt {
+            loc_opt = self.follow(loc);
+            self.at_mut(loc).unwrap().1 = Status::Path;
+            path.push(loc);
+            let step_cost = match loc_opt {
+                Some(prev) => self.step_cost(prev, loc),
+                None => self.at(loc).unwrap().0.cost(),
+            };
+            cost += step_cost;
+            out.event(Event::Backtrack { loc, step_cost });
+            if loc == self.start {
+                self.display_costs = false;
+            }
+            out.frame(self);
+        }
+        path.reverse();
+        (path, cost)
+    }
+
+    // The cost of moving from one spot onto a neighboring one
+    pub fn step_cost(&self, from: Vec2, to: Vec2) -> usize {
+        self.cost_onto(self.map[self.index(to)].0, from, to)
+    }
+
+    // The same for a move from step `t` to the next, onto the terrain as it is by then.
+    // Staying on a spot costs what the spot does.
+    pub fn step_cost_at(&self, from: Vec2, to: Vec2, t: usize) -> usize {
+        self.cost_onto(self.terrain_at(to, t + 1), from, to)
+    }
+
+    fn cost_onto(&self, terrain: Terrain, from: Vec2, to: Vec2) -> usize {
+        let cost = terrain.cost();
+        let cost = match self.diagonals {
+            Some(mult) if from.0 != to.0 && from.1 != to.1 => {
+                ((cost as f64 * mult).round() as usize).max(1)
+            }
+            _ => cost,
+        };
+        // going up costs extra, but going down costs the same as staying level, so no
+        // move costs less than its terrain and the distance heuristics still hold
+        cost + self.climb * self.height(to).saturating_sub(self.height(from))
+    }
+
+    // How high a spot is, or 0 on a flat map
+    pub fn height(&self, loc: Vec2) -> usize {
+        self.elevation
+            .get(self.index(loc))
+            .map_or(0, |&h| h as usize)
+    }
+
+    // The moves out of `loc`, which only depend on its row on hex maps
+    fn allowed_moves(&self, loc: Vec2) -> &'static [((isize, isize), Status)] {
+        match self.diagonals {
+            _ if self.hex => &HEX_MOVES[loc.1 % 2],
+            Some(_) => &MOVES,
+            None => &MOVES[..4],
+        }
+    }
+
+    // Every (dx, dy) that a single move out of `loc` can take
+    pub fn directions(&self, loc: Vec2) -> impl Iterator<Item = (isize, isize)> {
+        self.allowed_moves(loc).iter().map(|&(d, _)| d)
+    }
+
+    // The back-pointer left on `from` when it was reached from `to`
+    pub fn toward(&self, from: Vec2, to: Vec2, active: bool) -> Status {
+        let d = (
+            from.0 as isize - to.0 as isize,
+            from.1 as isize - to.1 as isize,
+        );
+        let moves = match self.hex {
+            true => &HEX_MOVES[to.1 % 2][..],
+            false => &MOVES[..],
+        };
+        let Some(&(_, mut status)) = moves.iter().find(|&&(m, _)| m == d) else {
+            panic!("Spots {from:?} and {to:?} aren't neighbors");
+        };
+        if !active {
+            status.deactivate();
+        }
+        status
+    }
+
+    // The distance that never overestimates the cost between two spots on this map
+    pub fn dist_mode(&self) -> DistMode {
+        match self.diagonals {
+            _ if self.hex => DistMode::Hex,
+            Some(diagonal) => DistMode::Octile(diagonal),
+            None => DistMode::TaxiCab,
+        }
+    }
+
+    pub fn go_neighbors(&self, loc: &Vec2) -> impl Iterator<Item = (Option<Vec2>, Status)> + '_ {
+        self.neighbors_where(loc, |status| status == Status::Untraversed)
+    }
+
+    // Like go_neighbors, but spots that are still on the frontier count too, so a
+    // cheaper way to one can be found before it's popped
+    pub fn open_neighbors(&self, loc: &Vec2) -> impl Iterator<Item = (Option<Vec2>, Status)> + '_ {
+        self.neighbors_where(loc, |status| {
+            status == Status::Untraversed || status.is_active()
+        })
+    }
+
+    fn neighbors_where(
+        &self,
+        loc: &Vec2,
+        open: impl Fn(Status) -> bool + 'static,
+    ) -> impl Iterator<Item = (Option<Vec2>, Status)> + '_ {
+        let loc = *loc;
+        self.allowed_moves(loc).iter().map(move |&(d, dir)| {
+            let loc_new = self.go(loc, d).filter(|&l| open(self.map[self.index(l)].1));
+            (loc_new, dir)
+        })
+    }
+
+    // Every spot that can be moved onto from `loc`, whether it's been traversed or not
+    pub fn moves(&self, loc: Vec2) -> Vec<Vec2> {
+        self.allowed_moves(loc)
+            .iter()
+            .filter_map(|&(d, _)| self.go(loc, d))
+            .collect()
+    }
+
+    // The spot one move away in direction (dx, dy), if that move is allowed
+    pub fn go(&self, loc: Vec2, d: (isize, isize)) -> Option<Vec2> {
+        self.go_where(loc, d, |l| self.at(l).is_some())
+    }
+
+    fn go_where(
+        &self,
+        loc: Vec2,
+        (dx, dy): (isize, isize),
+        open: impl Fn(Vec2) -> bool,
+    ) -> Option<Vec2> {
+        let loc_new = (loc.0.checked_add_signed(dx)?, loc.1.checked_add_signed(dy)?);
+        // diagonal moves can't cut the corner of a water (or off-map) spot
+        if !self.hex
+            && dx != 0
+            && dy != 0
+            && !(open((loc_new.0, loc.1)) && open((loc.0, loc_new.1)))
+        {
+            return None;
+        }
+        open(loc_new).then_some(loc_new)
+    }
+
+    // What a spot is made of at step `t`, once any tides that are in have come in (the
+    // later ones in the list win)
+    pub fn terrain_at(&self, loc: Vec2, t: usize) -> Terrain {
+        let tide = self.hazards.iter().rev().find_map(|hazard| match hazard {
+            Hazard::Tide { terrain, spots, .. } if hazard.tide_in(t) && spots.contains(&loc) => {
+                Some(*terrain)
+            }
+            _ => None,
+        });
+        tide.unwrap_or(self.map[self.index(loc)].0)
+    }
+
+    // Every spot that can be moved onto from `loc` with the terrain as it is at step `t`
+    pub fn moves_at(&self, loc: Vec2, t: usize) -> Vec<Vec2> {
+        let open =
+            |l: Vec2| l.0 < self.dim.0 && l.1 < self.dim.1 && self.terrain_at(l, t).passable();
+        self.directions(loc)
+            .filter_map(|d| self.go_where(loc, d, open))
+            .collect()
+    }
+
+    // Lay a path (start first) down as back-pointers, the way a search would have left it
+    pub fn trail(&mut self, path: &[Vec2]) {
+        if let Some(&start) = path.first() {
+            self.at_mut(start).unwrap().1 = Status::Path;
+        }
+        for pair in path.windows(2) {
+            let status = self.toward(pair[1], pair[0], false);
+            self.at_mut(pair[1]).unwrap().1 = status;
+        }
+    }
+}
+
+impl Debug for Map {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let string = self.map_text();
+        write!(f, "{string}")
+    }
+}
+
+impl Display for Map {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let string = self.map_text();
+        write!(f, "{string}")
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum DistMode {
+    TaxiCab,
+    Euclidean,
+    Octile(f64), // diagonal cost multiplier (see Map::step_cost)
+    Hex,         // steps between hexes (see HEX_MOVES)
+}
+
+pub fn dist(a: Vec2, b: Vec2, mode: DistMode) -> usize {
+    let d = (a.0.abs_diff(b.0), a.1.abs_diff(b.1));
+    match mode {
+        DistMode::TaxiCab => d.0 + d.1,
+        DistMode::Euclidean => ((d.0 * d.0 + d.1 * d.1) as f64).sqrt().floor() as usize,
+        DistMode::Octile(mult) => {
+            // a diagonal step onto a road is the cheapest, but a lower bound for it
+            // shouldn't exceed the two straight steps that could replace it
+            let diag = (mult.round() as usize).clamp(1, 2);
+            let (lo, hi) = (d.0.min(d.1), d.0.max(d.1));
+            (hi - lo) + diag * lo
+        }
+        DistMode::Hex => {
+            // shearing the rows back into line turns it into a distance on axial
+            // coordinates, where a step changes q, r or both by one in opposite ways
+            let q = |loc: Vec2| loc.0 as isize - (loc.1 / 2) as isize;
+            let (dq, dr) = (q(a) - q(b), a.1 as isize - b.1 as isize);
+            (dq.abs() + dr.abs() + (dq + dr).abs()) as usize / 2
+        }
+    }
+}
@@ -0,0 +1,404 @@
+use crate::{
+    map::{dist, Map, Status, Vec2},
+    search::{Algorithm, Counts, Outcome, Strategy},
+    trace::Event,
+    Output,
+};
+use priority_queue::PriorityQueue;
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet},
+};
+
+// Conflict based search: plans for several agents that can't be on the same spot at
+// the same time, or swap spots with each other in one step. Each agent is planned on
+// its own with a space-time A*, and whenever two plans conflict the search branches
+// on which of the two agents has to keep out of the way, keeping the branches with the
+// lowest total cost first.
+//
+// Every step an agent either moves, paying for the spot it moves onto, or waits,
+// paying for the spot it's on. Once at its goal it stays there for free.
+pub struct ConflictBased {
+    pub name: String,
+    pub title: String,
+    pub label: String,
+    pub failure: String,
+    pub max_nodes: usize, // how many branches to try before giving up
+}
+
+// Things an agent isn't allowed to do
+#[derive(Clone, Default)]
+struct Constraints {
+    at: HashSet<(Vec2, usize)>,          // be on a spot at a time
+    moves: HashSet<(Vec2, Vec2, usize)>, // move from one spot to another, arriving at a time
+}
+
+enum Ban {
+    At(Vec2, usize),
+    Move(Vec2, Vec2, usize),
+}
+
+impl Constraints {
+    fn add(&mut self, ban: Ban) {
+        match ban {
+            Ban::At(loc, t) => self.at.insert((loc, t)),
+            Ban::Move(from, to, t) => self.moves.insert((from, to, t)),
+        };
+    }
+}
+
+// A branch of the search: everyone's constraints and the plans that follow them
+struct Node {
+    constraints: Vec<Constraints>,
+    paths: Vec<Vec<Vec2>>, // each agent's spot at every time, until it stays at its goal
+    cost: usize,
+}
+
+enum Conflict {
+    Spot(usize, usize, Vec2, usize), // agents a and b both on a spot at a time
+    Swap(usize, usize, Vec2, Vec2, usize), // a moves from one spot to the other as b comes back
+}
+
+// Where an agent following `path` is at `t`
+fn at(path: &[Vec2], t: usize) -> Vec2 {
+    path[t.min(path.len() - 1)]
+}
+
+// The cost of a timed path, counting the spot it starts on like the other searches do
+fn path_cost(map: &Map, path: &[Vec2]) -> usize {
+    let start = map.map[map.index(path[0])].0.cost();
+    start
+        + path
+            .windows(2)
+            .map(|pair| step(map, pair[0], pair[1]))
+            .sum::<usize>()
+}
+
+fn step(map: &Map, from: Vec2, to: Vec2) -> usize {
+    if from == to {
+        map.map[map.index(to)].0.cost()
+    } else {
+        map.step_cost(from, to)
+    }
+}
+
+fn first_conflict(paths: &[Vec<Vec2>]) -> Option<Conflict> {
+    let end = paths.iter().map(|p| p.len()).max().unwrap_or(0);
+    for t in 0..end {
+        for a in 0..paths.len() {
+            for b in a + 1..paths.len() {
+                let (pa, pb) = (&paths[a], &paths[b]);
+                if at(pa, t) == at(pb, t) {
+                    return Some(Conflict::Spot(a, b, at(pa, t), t));
+                }
+                if at(pa, t) == at(pb, t + 1)
+                    && at(pa, t + 1) == at(pb, t)
+                    && at(pa, t) != at(pa, t + 1)
+                {
+                    return Some(Conflict::Swap(a, b, at(pa, t), at(pa, t + 1), t + 1));
+                }
+            }
+        }
+    }
+    None
+}
+
+// How many pairs of agents run into each other at some point, to break ties between
+// equally cheap branches in favor of the ones closer to done
+fn conflicts(paths: &[Vec<Vec2>]) -> usize {
+    let end = paths.iter().map(|p| p.len()).max().unwrap_or(0);
+    let mut count = 0;
+    for a in 0..paths.len() {
+        for b in a + 1..paths.len() {
+            let (pa, pb) = (&paths[a], &paths[b]);
+            count += (0..end).any(|t| {
+                at(pa, t) == at(pb, t) || (at(pa, t) == at(pb, t + 1) && at(pa, t + 1) == at(pb, t))
+            }) as usize;
+        }
+    }
+    count
+}
+
+impl ConflictBased {
+    pub fn new() -> Self {
+        Self {
+            name: "cbs".into(),
+            title: "conflict based search".into(),
+            label: "conflict based".into(),
+            failure: "Conflict based search failed!".into(),
+            max_nodes: 10_000,
+        }
+    }
+
+    // A* over (spot, time), so that an agent can wait out a constraint. Between equally
+    // cheap paths it prefers the one that runs into the `others`' paths the least. Returns
+    // the agent's spot at every time until it's at its goal for good.
+    fn space_time_a_star(
+        map: &Map,
+        start: Vec2,
+        goal: Vec2,
+        constraints: &Constraints,
+        others: &[&Vec<Vec2>],
+        counts: &mut Counts,
+        out: &mut Output,
+    ) -> Option<Vec<Vec2>> {
+        let mode = map.dist_mode();
+        // it can only stop at its goal once nobody needs it to be elsewhere
+        let last_banned = constraints
+            .at
+            .iter()
+            .filter(|&&(loc, _)| loc == goal)
+            .map(|&(_, t)| t + 1)
+            .max()
+            .unwrap_or(0);
+        let latest = constraints
+            .at
+            .iter()
+            .map(|&(_, t)| t)
+            .chain(constraints.moves.iter().map(|&(_, _, t)| t))
+            .max()
+            .unwrap_or(0);
+        let horizon = latest + map.dim.0 * map.dim.1;
+        if map.at(start).is_none() || map.at(goal).is_none() || constraints.at.contains(&(start, 0))
+        {
+            return None;
+        }
+
+        let bumps = |loc: Vec2, next: Vec2, t: usize| {
+            others
+                .iter()
+                .filter(|p| at(p, t + 1) == next || (at(p, t) == next && at(p, t + 1) == loc))
+                .count()
+        };
+
+        let mut g: HashMap<(Vec2, usize), (usize, usize)> = HashMap::new(); // (cost, bumps)
+        let mut from: HashMap<(Vec2, usize), Vec2> = HashMap::new();
+        let mut q = PriorityQueue::new();
+        let start_cost = map.at(start).unwrap().0.cost();
+        g.insert((start, 0), (start_cost, 0));
+        q.push((start, 0), Reverse((0, 0)));
+        counts.pushed(q.len());
+        out.event(Event::Push {
+            loc: start,
+            from: None,
+            g: start_cost,
+            priority: 0.0,
+        });
+        while let Some(((loc, t), Reverse((f, _)))) = q.pop() {
+            counts.pops += 1;
+            let cost = g[&(loc, t)].0;
+            out.event(Event::Pop {
+                loc,
+                g: cost,
+                priority: f as f64,
+            });
+            if loc == goal && t >= last_banned {
+                out.event(Event::Goal { loc, g: cost });
+                let mut path = vec![loc];
+                let mut state = (loc, t);
+                while let Some(&prev) = from.get(&state) {
+                    state = (prev, state.1 - 1);
+                    path.push(prev);
+                }
+                path.reverse();
+                return Some(path);
+            }
+            if t >= horizon {
+                continue;
+            }
+            let (cost, bumped) = g[&(loc, t)];
+            for next in map.moves(loc).into_iter().chain(std::iter::once(loc)) {
+                if constraints.at.contains(&(next, t + 1))
+                    || constraints.moves.contains(&(loc, next, t + 1))
+                {
+                    continue;
+                }
+                let maybe = (cost + step(map, loc, next), bumped + bumps(loc, next, t));
+                let old = g.get(&(next, t + 1)).copied();
+                if old.is_none_or(|old| maybe < old) {
+                    g.insert((next, t + 1), maybe);
+                    from.insert((next, t + 1), loc);
+                    let f = maybe.0 + dist(next, goal, mode);
+                    q.push((next, t + 1), Reverse((f, maybe.1)));
+                    counts.pushed(q.len());
+                    let known = old.map_or(usize::MAX, |(cost, _)| cost);
+                    out.event(Event::reached(known, next, loc, maybe.0, f as f64));
+                }
+            }
+        }
+        None
+    }
+
+    // Plans for every agent, as (start, goal) pairs, with no conflicts between them.
+    // Returns each agent's spot at every time, how many branches it took, and the work
+    // done by the searches for each agent.
+    pub fn plan(
+        &self,
+        map: &Map,
+        agents: &[(Vec2, Vec2)],
+        out: &mut Output,
+    ) -> (Option<Vec<Vec<Vec2>>>, usize, Counts) {
+        let mut nodes = vec![];
+        let mut open = BinaryHeap::new();
+        let mut expanded = 0;
+        let mut counts = Counts::default();
+
+        // Make sure everyone can get to their goal at all first, since the space-time
+        // search only finds out it can't after trying every time up to its horizon
+        for &(start, goal) in agents {
+            let mut alone = map.clone();
+            (alone.start, alone.goal) = (start, goal);
+            let outcome = Strategy::breadth_first().run(&alone, &mut Output::silent());
+            counts.add(&outcome);
+            if outcome.path.is_none() {
+                return (None, expanded, counts);
+            }
+        }
+
+        // Start with everyone planned on their own, keeping out of the way of the agents
+        // planned before them where it's free to
+        let constraints = vec![Constraints::default(); agents.len()];
+        let mut paths: Vec<Vec<Vec2>> = vec![];
+        for (&(start, goal), c) in agents.iter().zip(&constraints) {
+            let others: Vec<&Vec<Vec2>> = paths.iter().collect();
+            match Self::space_time_a_star(map, start, goal, c, &others, &mut counts, out) {
+                Some(path) => paths.push(path),
+                None => return (None, expanded, counts),
+            }
+        }
+        let cost = paths.iter().map(|p| path_cost(map, p)).sum();
+        open.push(Reverse((cost, conflicts(&paths), nodes.len())));
+        nodes.push(Some(Node {
+            constraints,
+            paths,
+            cost,
+        }));
+
+        while let Some(Reverse((_, _, id))) = open.pop() {
+            let node = nodes[id].take().unwrap();
+            expanded += 1;
+            let Some(conflict) = first_conflict(&node.paths) else {
+                return (Some(node.paths), expanded, counts);
+            };
+            if expanded >= self.max_nodes {
+                break;
+            }
+
+            // Branch on which of the two agents has to give way
+            let bans = match conflict {
+                Conflict::Spot(a, b, loc, t) => [(a, Ban::At(loc, t)), (b, Ban::At(loc, t))],
+                Conflict::Swap(a, b, x, y, t) => [(a, Ban::Move(x, y, t)), (b, Ban::Move(y, x, t))],
+            };
+            for (agent, ban) in bans {
+                let mut constraints = node.constraints.clone();
+                constraints[agent].add(ban);
+                let (start, goal) = agents[agent];
+                let others: Vec<&Vec<Vec2>> = (node.paths.iter().enumerate())
+                    .filter(|&(i, _)| i != agent)
+                    .map(|(_, p)| p)
+                    .collect();
+                let Some(path) = Self::space_time_a_star(
+                    map,
+                    start,
+                    goal,
+                    &constraints[agent],
+                    &others,
+                    &mut counts,
+                    out,
+                ) else {
+                    continue;
+                };
+                let mut paths = node.paths.clone();
+                let cost = node.cost - path_cost(map, &paths[agent]) + path_cost(map, &path);
+                paths[agent] = path;
+                open.push(Reverse((cost, conflicts(&paths), nodes.len())));
+                nodes.push(Some(Node {
+                    constraints,
+                    paths,
+                    cost,
+                }));
+            }
+        }
+        (None, expanded, counts)
+    }
+}
+
+impl Default for ConflictBased {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Algorithm for ConflictBased {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn run(&self, map: &Map, out: &mut Output) -> Outcome {
+        let agents: Vec<(Vec2, Vec2)> = std::iter::once((map.start, map.goal))
+            .chain(map.agents.iter().copied())
+            .collect();
+        out.text(&format!(
+            "Running {} ({} agents)\n",
+            self.title,
+            agents.len()
+        ));
+        let (paths, expanded, counts) = self.plan(map, &agents, out);
+        let pops = counts.pops;
+        out.text(&format!(
+            "Searched {expanded} branches ({pops} spots expanded)\n"
+        ));
+        let Some(paths) = paths else {
+            out.event(Event::Fail);
+            out.text(&format!("{} No valid paths exist\n", self.failure));
+            out.flush();
+            return Outcome {
+                path: None,
+                route: vec![],
+                pops,
+                generated: counts.generated,
+                max_frontier: counts.max_frontier,
+            };
+        };
+
+        // Lay every agent's path down with its own glyph (or a block where they cross)
+        let mut view = map.clone();
+        for (i, path) in paths.iter().enumerate() {
+            for &loc in path {
+                let status = &mut view.at_mut(loc).unwrap().1;
+                *status = match *status {
+                    Status::Agent(j) if j as usize != i => Status::Path,
+                    Status::Path => Status::Path,
+                    _ => Status::Agent(i as u16),
+                };
+            }
+        }
+        out.frame(&view);
+
+        let (mut dist, mut cost) = (0, 0);
+        for (i, path) in paths.iter().enumerate() {
+            let agent_cost = path_cost(map, path);
+            out.text(&format!(
+                "Agent {} (dist: {} cost: {agent_cost}) arrives at step {}\n",
+                i + 1,
+                path.len(),
+                path.len() - 1
+            ));
+            dist += path.len();
+            cost += agent_cost;
+        }
+        out.event(Event::Path { dist, cost });
+        out.text(&format!(
+            "Path found (dist: {dist} cost: {cost} iterations: {pops}) by {} alg\n",
+            self.label
+        ));
+        out.flush();
+        Outcome {
+            path: Some((dist, cost)),
+            route: paths.into_iter().next().unwrap(),
+            pops,
+            generated: counts.generated,
+            max_frontier: counts.max_frontier,
+        }
+    }
+}
@@ -0,0 +1,408 @@
+use crate::{
+    landmarks::Landmarks,
+    map::{dist, DistMode, Map, Status, Vec2, Visit},
+    trace::Event,
+    Output,
+};
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, VecDeque},
+};
+
+// ---- FRONTIERS ---- //
+pub trait Frontier {
+    fn push(&mut self, visit: Visit, priority: usize);
+    fn pop(&mut self) -> Option<(Visit, usize)>; // the visit and its priority
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[derive(Default)]
+pub struct Fifo(VecDeque<(Visit, usize)>);
+
+impl Frontier for Fifo {
+    fn push(&mut self, visit: Visit, priority: usize) {
+        self.0.push_back((visit, priority));
+    }
+
+    fn pop(&mut self) -> Option<(Visit, usize)> {
+        self.0.pop_front()
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+// A binary heap, lowest priority first. Ties go to whatever was pushed last, which
+// keeps a search heading the way it was already going.
+#[derive(Default)]
+pub struct Priority {
+    heap: BinaryHeap<(Reverse<usize>, usize, Visit)>,
+    pushed: usize,
+}
+
+impl Frontier for Priority {
+    fn push(&mut self, visit: Visit, priority: usize) {
+        self.pushed += 1;
+        self.heap.push((Reverse(priority), self.pushed, visit));
+    }
+
+    fn pop(&mut self) -> Option<(Visit, usize)> {
+        self.heap.pop().map(|(Reverse(p), _, v)| (v, p))
+    }
+
+    fn len(&self) -> usize {
+        self.heap.len()
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum FrontierKind {
+    Fifo,
+    Priority,
+}
+
+impl FrontierKind {
+    fn build(&self) -> Box<dyn Frontier> {
+        match self {
+            Self::Fifo => Box::<Fifo>::default(),
+            Self::Priority => Box::<Priority>::default(),
+        }
+    }
+}
+
+// ---- HEURISTICS ---- //
+pub enum Heuristic {
+    None,
+    Dist(DistMode),
+    Custom(Box<dyn Fn(Vec2, Vec2) -> usize>),
+}
+
+impl Heuristic {
+    pub fn estimate(&self, loc: Vec2, goal: Vec2) -> usize {
+        match self {
+            Self::None => 0,
+            Self::Dist(mode) => dist(loc, goal, *mode),
+            Self::Custom(h) => h(loc, goal),
+        }
+    }
+}
+
+// ---- STRATEGIES ---- //
+pub struct Strategy {
+    pub name: String,    // used for the results file name
+    pub title: String,   // "Running {title}"
+    pub label: String,   // "Path found (...) by {label} alg"
+    pub failure: String, // "{failure} No valid paths exist"
+    pub frontier: FrontierKind,
+    pub heuristic: Heuristic,
+    pub use_cost: bool, // whether the path cost so far is part of the priority
+}
+
+impl Strategy {
+    pub fn breadth_first() -> Self {
+        Self {
+            name: "breadth_first".into(),
+            title: "breadth first search".into(),
+            label: "breadth first".into(),
+            failure: "Breadth first search failed!".into(),
+            frontier: FrontierKind::Fifo,
+            heuristic: Heuristic::None,
+            use_cost: false,
+        }
+    }
+
+    pub fn lowest_cost_path() -> Self {
+        Self {
+            name: "lowest_cost".into(),
+            title: "lowest cost search".into(),
+            label: "lowest cost".into(),
+            failure: "Lowest cost search failed!".into(),
+            frontier: FrontierKind::Priority,
+            heuristic: Heuristic::None,
+            use_cost: true,
+        }
+    }
+
+    pub fn greedy_best_first() -> Self {
+        Self {
+            name: "greedy_best_first".into(),
+            title: "greedy best first search".into(),
+            label: "greedy best first".into(),
+            failure: "Greedy best first search failed!".into(),
+            frontier: FrontierKind::Priority,
+            heuristic: Heuristic::Dist(DistMode::TaxiCab),
+            use_cost: false,
+        }
+    }
+
+    pub fn a_star_taxicab() -> Self {
+        Self {
+            name: "a_star_1".into(),
+            title: "A* search (heuristic: taxicab dist)".into(),
+            label: "A* (taxicab)".into(),
+            failure: "A* search failed!".into(),
+            frontier: FrontierKind::Priority,
+            heuristic: Heuristic::Dist(DistMode::TaxiCab),
+            use_cost: true,
+        }
+    }
+
+    pub fn a_star_euclidean() -> Self {
+        Self {
+            name: "a_star_2".into(),
+            title: "A* search (heuristic: euclidean dist)".into(),
+            label: "A* (euclid)".into(),
+            failure: "A* search failed!".into(),
+            frontier: FrontierKind::Priority,
+            heuristic: Heuristic::Dist(DistMode::Euclidean),
+            use_cost: true,
+        }
+    }
+
+    pub fn a_star_octile(diagonal: f64) -> Self {
+        Self {
+            name: "a_star_3".into(),
+            title: "A* search (heuristic: octile dist)".into(),
+            label: "A* (octile)".into(),
+            failure: "A* search failed!".into(),
+            frontier: FrontierKind::Priority,
+            heuristic: Heuristic::Dist(DistMode::Octile(diagonal)),
+            use_cost: true,
+        }
+    }
+
+    pub fn a_star_hex() -> Self {
+        Self {
+            name: "a_star_4".into(),
+            title: "A* search (heuristic: hex dist)".into(),
+            label: "A* (hex)".into(),
+            failure: "A* search failed!".into(),
+            frontier: FrontierKind::Priority,
+            heuristic: Heuristic::Dist(DistMode::Hex),
+            use_cost: true,
+        }
+    }
+
+    pub fn a_star_landmarks(landmarks: Landmarks) -> Self {
+        Self {
+            name: "a_star_alt".into(),
+            title: format!("A* search (heuristic: {} landmarks)", landmarks.spots.len()),
+            label: "A* (landmarks)".into(),
+            failure: "A* search failed!".into(),
+            frontier: FrontierKind::Priority,
+            heuristic: Heuristic::Custom(Box::new(move |loc, goal| landmarks.estimate(loc, goal))),
+            use_cost: true,
+        }
+    }
+
+    fn priority(&self, cost: usize, loc: Vec2, goal: Vec2) -> usize {
+        let h = self.heuristic.estimate(loc, goal);
+        if self.use_cost {
+            cost.saturating_add(h)
+        } else {
+            h
+        }
+    }
+}
+
+// ---- THE ENGINE ---- //
+pub struct Outcome {
+    pub path: Option<(usize, usize)>, // (dist, cost)
+    pub route: Vec<Vec2>,             // the path itself, start first (empty if none)
+    pub pops: usize,
+    pub generated: usize,    // spots put on the frontier
+    pub max_frontier: usize, // the most spots that were on the frontier at once
+}
+
+// How much work a search has done so far
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Counts {
+    pub pops: usize,
+    pub generated: usize,
+    pub max_frontier: usize,
+}
+
+impl Counts {
+    // Count a spot being put on the frontier, which now holds `frontier` spots
+    pub fn pushed(&mut self, frontier: usize) {
+        self.generated += 1;
+        self.max_frontier = self.max_frontier.max(frontier);
+    }
+
+    // Add up the work of a search that was done as part of this one
+    pub fn add(&mut self, outcome: &Outcome) {
+        self.pops += outcome.pops;
+        self.generated += outcome.generated;
+        self.max_frontier = self.max_frontier.max(outcome.max_frontier);
+    }
+}
+
+// Anything that can be run on a map and report how it went
+pub trait Algorithm {
+    fn name(&self) -> &str;
+    fn run(&self, map: &Map, out: &mut Output) -> Outcome;
+}
+
+impl Algorithm for Strategy {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn run(&self, map: &Map, out: &mut Output) -> Outcome {
+        search(map, self, out)
+    }
+}
+
+pub fn search(map: &Map, strategy: &Strategy, out: &mut Output) -> Outcome {
+    // Variables
+    let mut done = false;
+    let mut map = map.clone();
+    let mut q = strategy.frontier.build();
+    let (start, goal) = (map.start, map.goal);
+    // FIFO searches are drawn once per layer, priority searches once per pop
+    let per_pop = strategy.frontier == FrontierKind::Priority;
+    let mut step_prev = 1;
+    let mut counts = Counts::default();
+
+    // Initialization
+    map.costs = vec![usize::MAX; map.dim.0 * map.dim.1];
+    map.display_costs = per_pop;
+    let i = map.index(start);
+    map.map[i].1 = Status::Path;
+    map.costs[i] = map.map[i].0.cost();
+    let g = map.costs[i];
+    let priority = strategy.priority(g, start, goal);
+    q.push(Visit::new(0, start, g), priority);
+    counts.pushed(q.len());
+    out.event(Event::Push {
+        loc: start,
+        from: None,
+        g,
+        priority: priority as f64,
+    });
+
+    // Loop
+    out.text(&format!("Running {}\n", strategy.title));
+    let mut neighbors = Vec::with_capacity(8);
+    'main_loop: while let Some((v, priority)) = q.pop() {
+        let (step, loc, cost) = (v.step, v.loc, v.cost);
+        // a cheaper visit to this spot was pushed after this one
+        if cost > map.costs[map.index(loc)] {
+            continue;
+        }
+        counts.pops += 1;
+        out.event(Event::Pop {
+            loc,
+            g: cost,
+            priority: priority as f64,
+        });
+        if per_pop || step != step_prev {
+            out.frame(&map);
+        }
+
+        map.at_mut(loc).unwrap().1.deactivate();
+        out.event(Event::Deactivate { loc });
+        if loc == goal {
+            out.event(Event::Goal { loc, g: cost });
+            done = true;
+            break 'main_loop;
+        }
+
+        // For each valid neighbor not yet popped, check if it would have been better to come
+        // from here. if so, update its cost and direction, then add it to the frontier.
+        // Searches that go by cost can find a cheaper way to a spot that's already on the
+        // frontier, so they only stop once the goal is popped. The others stop as soon as
+        // they see it.
+        neighbors.clear();
+        match strategy.use_cost {
+            true => neighbors.extend(map.open_neighbors(&loc)),
+            false => neighbors.extend(map.go_neighbors(&loc)),
+        };
+        for &n in &neighbors {
+            if let (Some(loc_new), dir) = n {
+                let maybe_cost = cost + map.step_cost(loc, loc_new);
+                let i = map.index(loc_new);
+                let known = map.costs[i];
+                if maybe_cost < known {
+                    map.costs[i] = maybe_cost;
+                    map.at_mut(loc_new).unwrap().1 = dir;
+                    let priority = strategy.priority(maybe_cost, loc_new, goal);
+                    q.push(Visit::new(step + 1, loc_new, maybe_cost), priority);
+                    counts.pushed(q.len());
+                    out.event(Event::reached(
+                        known,
+                        loc_new,
+                        loc,
+                        maybe_cost,
+                        priority as f64,
+                    ));
+                }
+                if loc_new == goal && !strategy.use_cost {
+                    out.event(Event::Goal {
+                        loc: goal,
+                        g: map.costs[map.index(goal)],
+                    });
+                    done = true;
+                    break 'main_loop;
+                }
+            }
+        }
+
+        step_prev = step;
+    }
+    if done {
+        while let Some((v, _)) = q.pop() {
+            counts.pops += 1;
+            map.at_mut(v.loc).unwrap().1.deactivate();
+            out.event(Event::Deactivate { loc: v.loc });
+        }
+        out.frame(&map);
+    }
+
+    report(
+        done.then_some(&mut map),
+        &strategy.label,
+        &strategy.failure,
+        counts,
+        out,
+    )
+}
+
+// Backtrack over a finished search (if it found the goal) and write up the result
+pub fn report(
+    map: Option<&mut Map>,
+    label: &str,
+    failure: &str,
+    counts: Counts,
+    out: &mut Output,
+) -> Outcome {
+    let pops = counts.pops;
+    let (path, route) = if let Some(map) = map {
+        // Now do backtracking
+        out.text("Doing backtracking\n");
+        let (route, cost) = map.backtrack(out);
+        let dist = route.len();
+        out.event(Event::Path { dist, cost });
+        out.text(&format!(
+            "Path found (dist: {dist} cost: {cost} iterations: {pops}) by {label} alg\n"
+        ));
+        (Some((dist, cost)), route)
+    } else {
+        out.event(Event::Fail);
+        out.text(&format!("{failure} No valid paths exist\n"));
+        (None, vec![])
+    };
+    out.flush();
+    Outcome {
+        path,
+        route,
+        pops,
+        generated: counts.generated,
+        max_frontier: counts.max_frontier,
+    }
+}
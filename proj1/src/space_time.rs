@@ -0,0 +1,256 @@
+use crate::{
+    map::{dist, Hazard, Map, Status, Vec2},
+    search::{Algorithm, Counts, Outcome},
+    trace::Event,
+    Output,
+};
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+};
+
+// A* over (spot, time), for maps with hazards that move or change on a schedule. Every
+// step the agent either moves, paying for the spot it moves onto as that spot is by
+// then, or waits where it is, paying for the spot it's on. It can't share a spot with
+// a patrol or pass one going the other way, and it can't be on a spot a tide has made
+// impassable.
+pub struct SpaceTime {
+    pub name: String,
+    pub title: String,
+    pub label: String,
+    pub failure: String,
+}
+
+// A spot, and where the hazards are in their cycles (see Clock::key)
+type State = (Vec2, usize);
+
+// When the hazards start repeating: from step `settle` on, they're the same every
+// `period` steps, so two visits to a spot at steps that are the same here are the same
+// as far as the rest of the search goes
+struct Clock {
+    settle: usize,
+    period: usize,
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+impl Clock {
+    fn new(hazards: &[Hazard]) -> Self {
+        let (mut settle, mut period) = (0, 1);
+        for hazard in hazards {
+            let cycle = match *hazard {
+                Hazard::Patrol(ref spots) => spots.len(),
+                Hazard::Tide {
+                    every: Some(every), ..
+                } => every,
+                Hazard::Tide { to, .. } => {
+                    settle = settle.max(to + 1);
+                    1
+                }
+            };
+            period = period / gcd(period, cycle) * cycle;
+        }
+        Self { settle, period }
+    }
+
+    fn key(&self, t: usize) -> usize {
+        if t < self.settle {
+            t
+        } else {
+            self.settle + (t - self.settle) % self.period
+        }
+    }
+}
+
+impl SpaceTime {
+    pub fn new() -> Self {
+        Self {
+            name: "space_time_a_star".into(),
+            title: "space-time A* search".into(),
+            label: "space-time A*".into(),
+            failure: "Space-time A* search failed!".into(),
+        }
+    }
+
+    // The agent's spot at every step from the start until it's at the goal, and what
+    // that cost, if it can get there at all
+    pub fn plan(
+        &self,
+        map: &Map,
+        counts: &mut Counts,
+        out: &mut Output,
+    ) -> Option<(Vec<Vec2>, usize)> {
+        let (start, goal) = (map.start, map.goal);
+        let mode = map.dist_mode();
+        let clock = Clock::new(&map.hazards);
+        let patrol_on =
+            |loc: Vec2, t: usize| map.hazards.iter().any(|h| h.patrol_at(t) == Some(loc));
+        // a patrol is on `to` by then, or is coming the other way
+        let blocked = |from: Vec2, to: Vec2, t: usize| {
+            patrol_on(to, t + 1)
+                || map.hazards.iter().any(|h| {
+                    from != to && h.patrol_at(t) == Some(to) && h.patrol_at(t + 1) == Some(from)
+                })
+        };
+        if !map.terrain_at(start, 0).passable() || patrol_on(start, 0) {
+            return None;
+        }
+
+        // the cost to each state, the step it's reached at, and the state before
+        let mut best: HashMap<State, (usize, usize, Option<State>)> = HashMap::new();
+        let mut q = BinaryHeap::new();
+        let g = map.terrain_at(start, 0).cost();
+        best.insert((start, 0), (g, 0, None));
+        q.push(Reverse((g + dist(start, goal, mode), 0, start)));
+        counts.pushed(q.len());
+        out.event(Event::Push {
+            loc: start,
+            from: None,
+            g,
+            priority: (g + dist(start, goal, mode)) as f64,
+        });
+        while let Some(Reverse((f, t, loc))) = q.pop() {
+            let key = clock.key(t);
+            let (cost, when, _) = best[&(loc, key)];
+            // a cheaper way to this state was pushed after this one
+            if when != t || cost + dist(loc, goal, mode) != f {
+                continue;
+            }
+            counts.pops += 1;
+            out.event(Event::Pop {
+                loc,
+                g: cost,
+                priority: f as f64,
+            });
+            if loc == goal {
+                out.event(Event::Goal { loc, g: cost });
+                let mut route = vec![loc];
+                let mut state = (loc, key);
+                while let Some((_, _, Some(prev))) = best.get(&state) {
+                    route.push(prev.0);
+                    state = *prev;
+                }
+                route.reverse();
+                return Some((route, cost));
+            }
+
+            let mut nexts = map.moves_at(loc, t + 1);
+            if map.terrain_at(loc, t + 1).passable() {
+                nexts.push(loc);
+            }
+            for next in nexts {
+                if blocked(loc, next, t) {
+                    continue;
+                }
+                let maybe = cost + map.step_cost_at(loc, next, t);
+                let state = (next, clock.key(t + 1));
+                let old = best.get(&state).map_or(usize::MAX, |&(old, _, _)| old);
+                if maybe < old {
+                    best.insert(state, (maybe, t + 1, Some((loc, key))));
+                    let f = maybe + dist(next, goal, mode);
+                    q.push(Reverse((f, t + 1, next)));
+                    counts.pushed(q.len());
+                    out.event(Event::reached(old, next, loc, maybe, f as f64));
+                }
+            }
+        }
+        None
+    }
+
+    // The map as it is at step `t` of the route, with the way there drawn in
+    fn view(map: &Map, route: &[Vec2], t: usize) -> Map {
+        let mut view = map.clone();
+        for hazard in &map.hazards {
+            if let Hazard::Tide { .. } = hazard {
+                for &loc in hazard.spots() {
+                    view.at_mut(loc).unwrap().0 = map.terrain_at(loc, t);
+                }
+            }
+        }
+        for &loc in &route[..t] {
+            view.at_mut(loc).unwrap().1 = Status::Path;
+        }
+        view.at_mut(route[t]).unwrap().1 = Status::Agent(0);
+        for hazard in &map.hazards {
+            if let Some(loc) = hazard.patrol_at(t) {
+                view.at_mut(loc).unwrap().1 = Status::Hazard;
+            }
+        }
+        view
+    }
+}
+
+impl Default for SpaceTime {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Algorithm for SpaceTime {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn run(&self, map: &Map, out: &mut Output) -> Outcome {
+        out.text(&format!(
+            "Running {} ({} hazards)\n",
+            self.title,
+            map.hazards.len()
+        ));
+        let mut counts = Counts::default();
+        let plan = self.plan(map, &mut counts, out);
+        let pops = counts.pops;
+        let Some((route, cost)) = plan else {
+            out.event(Event::Fail);
+            out.text(&format!("{} No valid paths exist\n", self.failure));
+            out.flush();
+            return Outcome {
+                path: None,
+                route: vec![],
+                pops,
+                generated: counts.generated,
+                max_frontier: counts.max_frontier,
+            };
+        };
+
+        // Show where everything is at each step of the way
+        let mut so_far = map.terrain_at(route[0], 0).cost();
+        for (t, &(x, y)) in route.iter().enumerate() {
+            let what = match t {
+                0 => "Start at",
+                _ if route[t - 1] == route[t] => "Wait at",
+                _ => "Move to",
+            };
+            if t > 0 {
+                so_far += map.step_cost_at(route[t - 1], route[t], t - 1);
+            }
+            out.text(&format!(
+                "Step {t}: {what} ({x}, {y}) (cost so far: {so_far})\n"
+            ));
+            if out.wants_frames() {
+                out.frame(&Self::view(map, &route, t));
+            }
+        }
+
+        let dist = route.len();
+        out.event(Event::Path { dist, cost });
+        out.text(&format!(
+            "Path found (dist: {dist} cost: {cost} iterations: {pops}) by {} alg\n",
+            self.label
+        ));
+        out.flush();
+        Outcome {
+            path: Some((dist, cost)),
+            route,
+            pops,
+            generated: counts.generated,
+            max_frontier: counts.max_frontier,
+        }
+    }
+}
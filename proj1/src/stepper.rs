@@ -0,0 +1,237 @@
+use crate::map::{Map, Status};
+use std::{
+    fs::File,
+    io::{Read, Write},
+    process::Command,
+    thread::sleep,
+    time::{Duration, Instant},
+};
+
+// What a key press asks the stepper to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Next,
+    Back,
+    Play,
+    Faster,
+    Slower,
+    Goal,
+    Costs,
+    First,
+    Last,
+    Quit,
+}
+
+// Playback speeds, in frames per second
+const SPEEDS: [u32; 7] = [1, 2, 5, 10, 20, 50, 100];
+
+const HELP: &str = "\
+→/n next  ←/p back  space play/pause  +/- speed  g goal  c costs  \
+home/s start  end/e end  q quit";
+
+impl Key {
+    // The keys pressed in `bytes`. Arrow keys and home/end come in as escape sequences.
+    pub fn parse(bytes: &[u8]) -> Vec<Key> {
+        let mut keys = vec![];
+        let mut i = 0;
+        while i < bytes.len() {
+            let (key, len) = match &bytes[i..] {
+                [0x1b, b'[', b'C', ..] => (Some(Key::Next), 3),
+                [0x1b, b'[', b'D', ..] => (Some(Key::Back), 3),
+                [0x1b, b'[', b'H', ..] => (Some(Key::First), 3),
+                [0x1b, b'[', b'F', ..] => (Some(Key::Last), 3),
+                [0x1b, b'[', _, ..] => (None, 3),
+                [b'n' | b'l' | b'.', ..] => (Some(Key::Next), 1),
+                [b'p' | b'h' | b',', ..] => (Some(Key::Back), 1),
+                [b' ', ..] => (Some(Key::Play), 1),
+                [b'+' | b'=', ..] => (Some(Key::Faster), 1),
+                [b'-' | b'_', ..] => (Some(Key::Slower), 1),
+                [b'g', ..] => (Some(Key::Goal), 1),
+                [b'c', ..] => (Some(Key::Costs), 1),
+                [b's', ..] => (Some(Key::First), 1),
+                [b'e', ..] => (Some(Key::Last), 1),
+                [b'q' | 0x1b | 0x03 | 0x04, ..] => (Some(Key::Quit), 1),
+                _ => (None, 1),
+            };
+            keys.extend(key);
+            i += len;
+        }
+        keys
+    }
+}
+
+// Steps back and forth through the frames a search drew
+pub struct Stepper {
+    frames: Vec<Map>,
+    pub at: usize,
+    pub playing: bool,
+    speed: usize, // index into SPEEDS
+    pub costs: bool,
+}
+
+impl Stepper {
+    pub fn new(frames: Vec<Map>) -> Self {
+        let costs = frames.first().is_some_and(|map| map.display_costs);
+        Self {
+            frames,
+            at: 0,
+            playing: false,
+            speed: 3,
+            costs,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub fn fps(&self) -> u32 {
+        SPEEDS[self.speed]
+    }
+
+    // The first frame that shows the goal settled, if the search got there: on the
+    // path, or reached and taken off the frontier (it's only been pushed while active)
+    pub fn goal_frame(&self) -> Option<usize> {
+        self.frames.iter().position(|map| {
+            map.at(map.goal).is_some_and(|spot| {
+                spot.1 == Status::Path || (spot.1 != Status::Untraversed && !spot.1.is_active())
+            })
+        })
+    }
+
+    pub fn press(&mut self, key: Key) {
+        let last = self.frames.len().saturating_sub(1);
+        match key {
+            Key::Next => self.at = (self.at + 1).min(last),
+            Key::Back => self.at = self.at.saturating_sub(1),
+            Key::Play if self.playing => self.playing = false,
+            Key::Play => {
+                // playing from the end starts over
+                if self.at == last {
+                    self.at = 0;
+                }
+                self.playing = self.at < last;
+            }
+            Key::Faster => self.speed = (self.speed + 1).min(SPEEDS.len() - 1),
+            Key::Slower => self.speed = self.speed.saturating_sub(1),
+            Key::Goal => self.at = self.goal_frame().unwrap_or(self.at),
+            Key::Costs => self.costs = !self.costs,
+            Key::First => self.at = 0,
+            Key::Last => self.at = last,
+            Key::Quit => (),
+        }
+        if !matches!(key, Key::Play | Key::Faster | Key::Slower | Key::Costs) {
+            self.playing = false;
+        }
+    }
+
+    // Move on a frame while playing, stopping at the end
+    pub fn tick(&mut self) {
+        if self.playing {
+            self.at += 1;
+            if self.at + 1 >= self.frames.len() {
+                self.at = self.frames.len().saturating_sub(1);
+                self.playing = false;
+            }
+        }
+    }
+
+    // The current frame, with a status line and the keys under it
+    pub fn screen(&self, title: &str) -> String {
+        let Some(frame) = self.frames.get(self.at) else {
+            return format!("{title}: nothing to show\n");
+        };
+        let mut map = frame.clone();
+        // costs can only be shown if the search kept them
+        map.display_costs = self.costs && map.costs.len() == map.dim.0 * map.dim.1;
+        let goal = match self.goal_frame() {
+            Some(n) => format!("goal at {n}"),
+            None => "goal not reached".into(),
+        };
+        format!(
+            "{title}\n{}frame {}/{}  {}  {} fps  {goal}  costs {}\n{HELP}\n",
+            map.map_text(),
+            self.at,
+            self.frames.len() - 1,
+            if self.playing { "playing" } else { "paused" },
+            self.fps(),
+            if map.display_costs { "on" } else { "off" },
+        )
+    }
+
+    // Take over the terminal until q is pressed
+    pub fn run(&mut self, title: &str) -> Result<(), String> {
+        let mut tty = File::open("/dev/tty").map_err(|e| format!("No terminal to use: {e}"))?;
+        let _raw = RawMode::enter()?;
+        let mut stdout = std::io::stdout();
+        let mut drawn = None;
+        let mut last_step = Instant::now();
+        let mut buf = [0; 64];
+        loop {
+            if drawn != Some((self.at, self.playing, self.speed, self.costs)) {
+                // draw over the last screen from the top, clearing what's left of it
+                let screen = self.screen(title).replace('\n', "\x1b[K\n");
+                write!(stdout, "\x1b[H{screen}\x1b[J")
+                    .and_then(|_| stdout.flush())
+                    .map_err(|e| e.to_string())?;
+                drawn = Some((self.at, self.playing, self.speed, self.costs));
+            }
+            let n = tty.read(&mut buf).map_err(|e| e.to_string())?;
+            for key in Key::parse(&buf[..n]) {
+                if key == Key::Quit {
+                    return Ok(());
+                }
+                self.press(key);
+                last_step = Instant::now();
+            }
+            let step = Duration::from_secs(1) / self.fps();
+            if self.playing && last_step.elapsed() >= step {
+                self.tick();
+                last_step = Instant::now();
+            }
+            if n == 0 {
+                sleep(Duration::from_millis(5));
+            }
+        }
+    }
+}
+
+// Key presses come in one at a time and don't echo while this is around. Reads don't
+// wait for input either, so playback can go on between presses.
+struct RawMode {
+    saved: String,
+}
+
+fn stty(args: &[&str]) -> Result<String, String> {
+    let tty = File::open("/dev/tty").map_err(|e| format!("No terminal to use: {e}"))?;
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(tty)
+        .output()
+        .map_err(|e| format!("Couldn't run stty: {e}"))?;
+    if !output.status.success() {
+        return Err("Couldn't set up the terminal".into());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().into())
+}
+
+impl RawMode {
+    fn enter() -> Result<Self, String> {
+        let saved = stty(&["-g"])?;
+        stty(&["-icanon", "-echo", "min", "0", "time", "0"])?;
+        print!("\x1b[2J\x1b[?25l"); // clear the screen and hide the cursor
+        Ok(Self { saved })
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        let _ = stty(&[&self.saved]);
+        println!("\x1b[?25h");
+        let _ = std::io::stdout().flush();
+    }
+}
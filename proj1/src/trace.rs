@@ -0,0 +1,140 @@
+use crate::map::Vec2;
+
+// Something a search did, for writing to a trace file one JSON object per line.
+// `g` is the cost of the best path found to the spot so far (counting the start's
+// terrain), and `priority` is what the frontier orders it by.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Event<'a> {
+    Start {
+        algorithm: &'a str,
+        dim: Vec2,
+        start: Vec2,
+        goal: Vec2,
+    },
+    Pop {
+        loc: Vec2,
+        g: usize,
+        priority: f64,
+    },
+    // put on the frontier for the first time (the start comes from nowhere)
+    Push {
+        loc: Vec2,
+        from: Option<Vec2>,
+        g: usize,
+        priority: f64,
+    },
+    // a cheaper way to a spot that was already reached
+    Relax {
+        loc: Vec2,
+        from: Vec2,
+        g: usize,
+        priority: f64,
+    },
+    // taken off the frontier for good
+    Deactivate {
+        loc: Vec2,
+    },
+    Goal {
+        loc: Vec2,
+        g: usize,
+    },
+    // the path traced back from the goal, one spot at a time, with what moving onto it
+    // cost
+    Backtrack {
+        loc: Vec2,
+        step_cost: usize,
+    },
+    Path {
+        dist: usize,
+        cost: usize,
+    },
+    Fail,
+}
+
+fn pair((x, y): Vec2) -> String {
+    format!("[{x}, {y}]")
+}
+
+impl Event<'_> {
+    // A push if `loc` hadn't been reached before (its old cost is usize::MAX), or else
+    // a relax
+    pub fn reached(old: usize, loc: Vec2, from: Vec2, g: usize, priority: f64) -> Self {
+        match old {
+            usize::MAX => Event::Push {
+                loc,
+                from: Some(from),
+                g,
+                priority,
+            },
+            _ => Event::Relax {
+                loc,
+                from,
+                g,
+                priority,
+            },
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Event::Start { .. } => "start",
+            Event::Pop { .. } => "pop",
+            Event::Push { .. } => "push",
+            Event::Relax { .. } => "relax",
+            Event::Deactivate { .. } => "deactivate",
+            Event::Goal { .. } => "goal",
+            Event::Backtrack { .. } => "backtrack",
+            Event::Path { .. } => "path",
+            Event::Fail => "fail",
+        }
+    }
+
+    pub fn json(&self) -> String {
+        let fields = match *self {
+            Event::Start {
+                algorithm,
+                dim,
+                start,
+                goal,
+            } => format!(
+                ", \"algorithm\": \"{}\", \"width\": {}, \"height\": {}, \"start\": {}, \"goal\": {}",
+                algorithm.replace('\\', "\\\\").replace('"', "\\\""),
+                dim.0,
+                dim.1,
+                pair(start),
+                pair(goal)
+            ),
+            Event::Pop { loc, g, priority } => {
+                format!(", \"loc\": {}, \"g\": {g}, \"priority\": {priority}", pair(loc))
+            }
+            Event::Push {
+                loc,
+                from,
+                g,
+                priority,
+            } => format!(
+                ", \"loc\": {}, \"from\": {}, \"g\": {g}, \"priority\": {priority}",
+                pair(loc),
+                from.map_or("null".into(), pair)
+            ),
+            Event::Relax {
+                loc,
+                from,
+                g,
+                priority,
+            } => format!(
+                ", \"loc\": {}, \"from\": {}, \"g\": {g}, \"priority\": {priority}",
+                pair(loc),
+                pair(from)
+            ),
+            Event::Deactivate { loc } => format!(", \"loc\": {}", pair(loc)),
+            Event::Goal { loc, g } => format!(", \"loc\": {}, \"g\": {g}", pair(loc)),
+            Event::Backtrack { loc, step_cost } => {
+                format!(", \"loc\": {}, \"step_cost\": {step_cost}", pair(loc))
+            }
+            Event::Path { dist, cost } => format!(", \"dist\": {dist}, \"cost\": {cost}"),
+            Event::Fail => String::new(),
+        };
+        format!("{{\"event\": \"{}\"{fields}}}", self.name())
+    }
+}
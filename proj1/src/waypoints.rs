@@ -0,0 +1,255 @@
+use crate::{
+    map::{Map, Vec2},
+    search::{Algorithm, Counts, Outcome, Strategy},
+    trace::Event,
+    Output,
+};
+
+// Routes from the start through every waypoint to the goal. The cheapest path between
+// each pair of stops comes from the lowest cost search, and then picking the order to
+// visit the waypoints in is a traveling salesman problem. It's solved exactly for up to
+// `exact_limit` waypoints, and past that with a nearest neighbor tour cleaned up by 2-opt.
+pub struct WaypointRoute {
+    pub name: String,
+    pub title: String,
+    pub label: String,
+    pub failure: String,
+    pub exact_limit: usize,
+}
+
+impl WaypointRoute {
+    pub fn new() -> Self {
+        Self {
+            name: "waypoints".into(),
+            title: "waypoint route".into(),
+            label: "waypoint route".into(),
+            failure: "Waypoint route failed!".into(),
+            exact_limit: 10,
+        }
+    }
+}
+
+impl Default for WaypointRoute {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// The cost of walking a path, not counting the spot it starts on
+fn leg_cost(map: &Map, path: &[Vec2]) -> usize {
+    path.windows(2)
+        .map(|pair| map.step_cost(pair[0], pair[1]))
+        .sum()
+}
+
+// The cost of going from stop 0 through `order` to the last stop
+fn tour_cost(costs: &[Vec<usize>], order: &[usize]) -> usize {
+    let last = costs.len() - 1;
+    std::iter::once(0)
+        .chain(order.iter().copied())
+        .zip(order.iter().copied().chain(std::iter::once(last)))
+        .fold(0, |total: usize, (a, b)| total.saturating_add(costs[a][b]))
+}
+
+// The cheapest order, by dynamic programming over every subset of the waypoints
+// (Held-Karp): best[set][j] is the cheapest way to visit `set` from the start, ending at j
+fn exact_order(costs: &[Vec<usize>]) -> Vec<usize> {
+    let n = costs.len() - 2;
+    let full = (1 << n) - 1;
+    let mut best = vec![vec![usize::MAX; n]; 1 << n];
+    let mut prev = vec![vec![usize::MAX; n]; 1 << n];
+    for j in 0..n {
+        best[1 << j][j] = costs[0][j + 1];
+    }
+    for set in 1..=full {
+        for j in (0..n).filter(|j| set & (1 << j) != 0) {
+            if best[set][j] == usize::MAX {
+                continue;
+            }
+            for k in (0..n).filter(|k| set & (1 << k) == 0) {
+                let cost = best[set][j].saturating_add(costs[j + 1][k + 1]);
+                if cost < best[set | 1 << k][k] {
+                    best[set | 1 << k][k] = cost;
+                    prev[set | 1 << k][k] = j;
+                }
+            }
+        }
+    }
+
+    // Work back from whichever waypoint is cheapest to finish from
+    let Some(mut j) = (0..n).min_by_key(|&j| best[full][j].saturating_add(costs[j + 1][n + 1]))
+    else {
+        return vec![];
+    };
+    let mut set = full;
+    let mut order = vec![];
+    while set != 0 {
+        order.push(j + 1);
+        let j_prev = prev[set][j];
+        set &= !(1 << j);
+        j = j_prev;
+    }
+    order.reverse();
+    order
+}
+
+// A good order: always head for the closest waypoint left, then keep reversing stretches
+// of the tour while that makes it cheaper (2-opt)
+fn heuristic_order(costs: &[Vec<usize>]) -> Vec<usize> {
+    let n = costs.len() - 2;
+    let mut left: Vec<usize> = (1..=n).collect();
+    let mut order = vec![];
+    let mut at = 0;
+    while let Some(i) = (0..left.len()).min_by_key(|&i| costs[at][left[i]]) {
+        at = left.swap_remove(i);
+        order.push(at);
+    }
+
+    let mut cost = tour_cost(costs, &order);
+    let mut improved = true;
+    while improved {
+        improved = false;
+        for i in 0..n {
+            for j in i + 1..n {
+                order[i..=j].reverse();
+                let new_cost = tour_cost(costs, &order);
+                if new_cost < cost {
+                    cost = new_cost;
+                    improved = true;
+                } else {
+                    order[i..=j].reverse();
+                }
+            }
+        }
+    }
+    order
+}
+
+impl Algorithm for WaypointRoute {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn run(&self, map: &Map, out: &mut Output) -> Outcome {
+        // Variables
+        let stops: Vec<Vec2> = std::iter::once(map.start)
+            .chain(map.waypoints.iter().copied())
+            .chain(std::iter::once(map.goal))
+            .collect();
+        let k = stops.len();
+        let mut paths: Vec<Vec<Option<Vec<Vec2>>>> = vec![vec![None; k]; k];
+        let mut costs = vec![vec![usize::MAX; k]; k];
+        let mut searches = 0;
+        let mut counts = Counts::default();
+        let fail = |out: &mut Output, counts: Counts| {
+            out.event(Event::Fail);
+            out.text(&format!("{} No valid paths exist\n", self.failure));
+            out.flush();
+            Outcome {
+                path: None,
+                route: vec![],
+                pops: counts.pops,
+                generated: counts.generated,
+                max_frontier: counts.max_frontier,
+            }
+        };
+
+        // Find the cheapest path between every pair of stops, each way, since going up a
+        // hill or into a diagonal costs something different on the way back
+        out.text(&format!(
+            "Running {} ({} waypoints)\n",
+            self.title,
+            map.waypoints.len()
+        ));
+        if stops.iter().any(|&stop| map.at(stop).is_none()) {
+            return fail(out, counts);
+        }
+        for i in 0..k {
+            for j in (0..k).filter(|&j| j != i) {
+                let path = if stops[i] == stops[j] {
+                    Some(vec![stops[i]])
+                } else {
+                    let mut leg = map.clone();
+                    leg.start = stops[i];
+                    leg.goal = stops[j];
+                    let mut nested = out.nested();
+                    let outcome = Strategy::lowest_cost_path().run(&leg, &mut nested);
+                    out.unnest(nested);
+                    searches += 1;
+                    counts.add(&outcome);
+                    outcome.path.map(|_| outcome.route)
+                };
+                if let Some(path) = path {
+                    costs[i][j] = leg_cost(map, &path);
+                    paths[i][j] = Some(path);
+                }
+            }
+        }
+        out.text(&format!(
+            "Found the paths between {k} stops ({searches} searches)\n"
+        ));
+
+        // Every stop has to be reachable from the start, and then they all reach each other
+        if costs[0][1..].contains(&usize::MAX) {
+            return fail(out, counts);
+        }
+
+        // Pick the order
+        let n = map.waypoints.len();
+        let (order, how) = if n <= self.exact_limit {
+            (exact_order(&costs), "exact")
+        } else {
+            (heuristic_order(&costs), "nearest neighbor and 2-opt")
+        };
+        let visits: Vec<usize> = std::iter::once(0)
+            .chain(order.iter().copied())
+            .chain(std::iter::once(k - 1))
+            .collect();
+        let names: Vec<String> = visits
+            .iter()
+            .map(|&v| match v {
+                0 => "S".to_string(),
+                v if v == k - 1 => "G".to_string(),
+                v => v.to_string(),
+            })
+            .collect();
+        let start_cost = map.at(map.start).unwrap().0.cost();
+        out.text(&format!(
+            "Visit order ({how}): {} (cost: {})\n",
+            names.join(" -> "),
+            start_cost + tour_cost(&costs, &order)
+        ));
+
+        // Backtrack each leg in turn onto one map
+        out.text("Doing backtracking\n");
+        let mut view = map.clone();
+        let mut route = vec![map.start];
+        let mut cost = start_cost;
+        for pair in visits.windows(2) {
+            let path = paths[pair[0]][pair[1]].as_ref().unwrap();
+            view.start = stops[pair[0]];
+            view.goal = stops[pair[1]];
+            view.trail(path);
+            view.backtrack(out);
+            cost += leg_cost(map, path);
+            route.extend(&path[1..]);
+        }
+        view.start = map.start;
+        view.goal = map.goal;
+        out.frame(&view);
+        let dist = route.len();
+        out.event(Event::Path { dist, cost });
+        out.text(&format!(
+            "Path found (dist: {dist} cost: {cost} iterations: {}) by {} alg\n",
+            counts.pops, self.label
+        ));
+        out.flush();
+        Outcome {
+            path: Some((dist, cost)),
+            route,
+            pops: counts.pops,
+            generated: counts.generated,
+            max_frontier: counts.max_frontier,
+        }
+    }
+}
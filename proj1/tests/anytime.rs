@@ -0,0 +1,38 @@
+mod common;
+
+use common::Shared;
+use proj1::{
+    anytime::Anytime,
+    map::Map,
+    search::{Algorithm, Outcome},
+    Output,
+};
+use std::time::Duration;
+
+// Run ARA* with an expansion budget, returning the outcome and what it wrote
+fn run(map: &Map, max_expansions: usize) -> (Outcome, String) {
+    let sink = Shared::default();
+    let mut out = Output::new(Box::new(sink.clone()));
+    (out.echo, out.frames, out.delay) = (false, false, Duration::ZERO);
+    let alg = Anytime::ara_star(map.dist_mode(), 3.0, 1.0, Some(max_expansions), None);
+    let outcome = alg.run(map, &mut out);
+    (outcome, sink.text())
+}
+
+#[test]
+fn running_out_of_budget_is_not_a_missing_path() {
+    let map = Map::from_file_path("data/map.txt").unwrap();
+    let (outcome, text) = run(&map, 5);
+    assert!(outcome.path.is_none());
+    assert!(
+        text.contains("ARA* search failed! Ran out of budget before any path was found"),
+        "{text}"
+    );
+    assert!(!text.contains("No valid paths exist"), "{text}");
+
+    // with enough for the first pass it keeps that path
+    let (outcome, text) = run(&map, 120);
+    assert!(outcome.path.is_some());
+    assert!(text.contains("Ran out of budget\n"), "{text}");
+    assert!(text.contains("Path found"), "{text}");
+}
@@ -0,0 +1,34 @@
+use proj1::{
+    bench::{self, Format},
+    map::Map,
+    search::Strategy,
+};
+
+#[test]
+fn rows_have_every_column() {
+    let map = Map::from_file_path("data/map-small-1.txt").unwrap();
+    let row = bench::measure("map-small-1", &map, &Strategy::lowest_cost_path());
+    assert_eq!(row.path, Some((9, 24)));
+    assert!(row.generated > 0 && row.max_frontier <= row.generated);
+
+    let csv = bench::line(&row, Format::Csv, true);
+    let fields: Vec<&str> = csv.trim_end().split(',').collect();
+    assert_eq!(fields.len(), 8);
+    assert_eq!(fields[..4], ["map-small-1", "lowest_cost", "9", "24"]);
+    assert_eq!(fields[4], row.pops.to_string());
+
+    let json = bench::line(&row, Format::Json, false);
+    assert!(json.starts_with(",\n  {\"map\": \"map-small-1\", \"algorithm\": \"lowest_cost\""));
+    assert!(json.contains("\"path_length\": 9, \"path_cost\": 24"));
+}
+
+#[test]
+fn failed_runs_have_no_path() {
+    let mut map = Map::from_file_path("data/map-small-1.txt").unwrap();
+    map.goal = (2, 1); // in the water
+    let row = bench::measure("lake", &map, &Strategy::breadth_first());
+    assert_eq!(row.path, None);
+    assert!(bench::line(&row, Format::Csv, true).starts_with("lake,breadth_first,,,"));
+    assert!(bench::line(&row, Format::Json, true)
+        .contains("\"path_length\": null, \"path_cost\": null"));
+}
@@ -0,0 +1,70 @@
+use proj1::{
+    d_star_lite::{DStarLite, Planner},
+    map::{Edit, Map, Terrain, TerrainTable},
+    search::{Algorithm, Strategy},
+    Output,
+};
+
+#[test]
+fn edit_script_parses() {
+    let edits =
+        Edit::script_from_file_path("data/map-edits.txt", &TerrainTable::default()).unwrap();
+    assert_eq!(edits.len(), 5);
+    assert_eq!(
+        edits[0],
+        Edit {
+            step: 4,
+            loc: (1, 11),
+            terrain: Terrain::WATER
+        }
+    );
+    assert_eq!(edits[3].terrain, Terrain::WATER);
+    assert_eq!(edits[4].terrain, Terrain::RIVER);
+}
+
+#[test]
+fn replanning_matches_searching_from_scratch() {
+    let map = Map::from_file_path("data/map.txt").unwrap();
+    let edits = Edit::script_from_file_path("data/map-edits.txt", &map.terrains).unwrap();
+    let mut out = Output::silent();
+    let mut planner = Planner::new(&map, &mut out);
+    let mut step = 0;
+    while planner.position != map.goal {
+        for edit in edits.iter().filter(|e| e.step == step) {
+            planner.edit(edit.loc, edit.terrain, &mut out);
+        }
+        planner.plan(&mut out);
+
+        let mut fresh = planner.map.clone();
+        fresh.start = planner.position;
+        let lowest = Strategy::lowest_cost_path().run(&fresh, &mut Output::silent());
+        assert_eq!(
+            planner.cost(),
+            lowest.path.map(|(_, cost)| cost),
+            "path costs differ at step {step}"
+        );
+
+        let path = planner.path().unwrap();
+        planner.advance(path[1]);
+        step += 1;
+    }
+}
+
+#[test]
+fn walled_off_goal_fails() {
+    let map = Map::from_file_path("data/map-small-1.txt").unwrap();
+    let edits = vec![
+        Edit {
+            step: 2,
+            loc: (3, 0),
+            terrain: Terrain::WATER,
+        },
+        Edit {
+            step: 2,
+            loc: (4, 1),
+            terrain: Terrain::WATER,
+        },
+    ];
+    let outcome = DStarLite::new(edits).run(&map, &mut Output::silent());
+    assert!(outcome.path.is_none());
+}
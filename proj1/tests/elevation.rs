@@ -0,0 +1,65 @@
+use proj1::{
+    jump_point::JumpPoint,
+    map::{Map, MapErrorKind},
+    search::{Algorithm, Strategy},
+    Output,
+};
+
+#[test]
+fn uphill_costs_more_than_downhill() {
+    let mut map = Map::from_text("3 1\n0 0\n2 0\nfff\nelevation\n025\n").unwrap();
+    assert_eq!((map.height((0, 0)), map.height((2, 0))), (0, 5));
+    assert_eq!(map.step_cost((0, 0), (1, 0)), 2 + 2);
+    assert_eq!(map.step_cost((1, 0), (2, 0)), 2 + 3);
+    assert_eq!(map.step_cost((2, 0), (1, 0)), 2);
+    assert_eq!(map.step_cost((1, 0), (0, 0)), 2);
+    map.climb = 3;
+    assert_eq!(map.step_cost((1, 0), (2, 0)), 2 + 9);
+
+    let up = Strategy::lowest_cost_path().run(&map, &mut Output::silent());
+    std::mem::swap(&mut map.start, &mut map.goal);
+    let down = Strategy::lowest_cost_path().run(&map, &mut Output::silent());
+    assert_eq!(up.path, Some((3, 2 + 8 + 11)));
+    assert_eq!(down.path, Some((3, 6)));
+}
+
+#[test]
+fn searches_go_around_hills() {
+    let mut map = Map::from_file_path("data/map-hills.txt").unwrap();
+    map.climb = 3;
+    let mut flat = map.clone();
+    flat.elevation.clear();
+    let highest = |route: Vec<(usize, usize)>| route.iter().map(|&l| map.height(l)).max();
+    for alg in [Strategy::lowest_cost_path(), Strategy::a_star_taxicab()] {
+        let hilly = alg.run(&map, &mut Output::silent()).route;
+        let straight = alg.run(&flat, &mut Output::silent()).route;
+        assert!(highest(hilly) < highest(straight), "{}", alg.name());
+    }
+    // jump point search can't jump over a change in height
+    let jump = JumpPoint::new(map.dist_mode()).run(&map, &mut Output::silent());
+    let cheapest = Strategy::lowest_cost_path().run(&map, &mut Output::silent());
+    assert_eq!(jump.path.unwrap().1, cheapest.path.unwrap().1);
+}
+
+#[test]
+fn elevation_rows_are_checked_and_saved() {
+    let err = Map::from_text("3 2\n0 0\n2 1\nfff\nfff\nelevation\n012\n0x2\n").unwrap_err();
+    assert_eq!(err.kind, MapErrorKind::UnknownHeight);
+    assert_eq!(
+        (err.line, err.column, err.character),
+        (Some(8), Some(2), Some('x'))
+    );
+    let err = Map::from_text("3 2\n0 0\n2 1\nfff\nfff\nelevation\n012\n").unwrap_err();
+    assert_eq!(err.kind, MapErrorKind::MissingRows(1));
+
+    let map = Map::from_file_path("data/map-hills.txt").unwrap();
+    assert_eq!(
+        Map::from_text(&map.file_text()).unwrap().elevation,
+        map.elevation
+    );
+    // maps without the layer are flat
+    assert!(Map::from_file_path("data/map.txt")
+        .unwrap()
+        .elevation
+        .is_empty());
+}
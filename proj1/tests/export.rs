@@ -0,0 +1,61 @@
+use proj1::{
+    export::{terrain_color, Recording, Shade},
+    map::{Map, Status, Terrain},
+    search::{Algorithm, Strategy},
+    Output,
+};
+use std::time::Duration;
+
+fn recorded(map: &Map) -> Recording {
+    let mut out = Output::silent();
+    out.recording = Some(Recording::new());
+    Strategy::lowest_cost_path().run(map, &mut out);
+    out.recording.unwrap()
+}
+
+#[test]
+fn statuses_map_to_shades() {
+    assert_eq!(Shade::of(Status::Untraversed), Shade::Unseen);
+    assert_eq!(Shade::of(Status::Left(true)), Shade::Frontier);
+    assert_eq!(Shade::of(Status::Left(false)), Shade::Visited);
+    assert_eq!(Shade::of(Status::Path), Shade::Path);
+    assert_eq!(Shade::of(Status::Agent(2)), Shade::Path);
+    assert_ne!(terrain_color(Terrain::ROAD), terrain_color(Terrain::WATER));
+}
+
+#[test]
+fn svg_has_a_cell_and_a_marker_per_spot() {
+    let map = Map::from_file_path("data/map-small-2.txt").unwrap();
+    let recording = recorded(&map);
+    assert!(recording.len() > 2);
+    let svg = recording.svg(Duration::from_millis(50));
+    assert!(svg.starts_with("<svg") && svg.ends_with("</svg>\n"));
+    let spots = map.dim.0 * map.dim.1;
+    assert_eq!(svg.matches("<rect").count(), 2 * spots);
+    assert!(svg.contains(&format!("fill=\"{}\"", terrain_color(Terrain::FOREST))));
+    // every path spot gets its marker turned red by the end
+    let route = Strategy::lowest_cost_path()
+        .run(&map, &mut Output::silent())
+        .route;
+    for (x, y) in route {
+        let marker = &svg[svg.find(&format!("id=\"m{}\"", y * map.dim.0 + x)).unwrap()..];
+        let marker = &marker[..marker.find("</rect>").unwrap()];
+        let last_set = marker.rsplit("<set").next().unwrap();
+        assert!(last_set.contains("to=\"#e53935\""), "{marker}");
+    }
+}
+
+#[test]
+fn html_has_every_frame() {
+    let map = Map::from_file_path("data/map-small-1.txt").unwrap();
+    let recording = recorded(&map);
+    let html = recording.html("lowest_cost");
+    assert!(html.contains("<title>lowest_cost</title>"));
+    assert!(html.contains(&format!("max=\"{}\"", recording.len() - 1)));
+    let spots = map.dim.0 * map.dim.1;
+    let frames = html
+        .lines()
+        .filter(|l| l.starts_with('"') && l.trim_end_matches(',').len() == spots + 2)
+        .count();
+    assert_eq!(frames, recording.len());
+}
@@ -0,0 +1,69 @@
+use proj1::{
+    fog::Explorer,
+    generator::Generator,
+    map::Map,
+    search::{Algorithm, Strategy},
+    Output,
+};
+
+#[test]
+fn walks_to_the_goal_and_pays_the_real_costs() {
+    for seed in 0..12 {
+        let mut map = Generator::new(30, 20, seed).generate();
+        if seed % 3 == 1 {
+            map.diagonals = Some(1.5);
+        }
+        let lowest = Strategy::lowest_cost_path().run(&map, &mut Output::silent());
+        let outcome = Explorer::new(1 + seed as usize % 3, 1).run(&map, &mut Output::silent());
+        assert_eq!(outcome.path.is_some(), lowest.path.is_some(), "seed {seed}");
+        let (Some((dist, cost)), Some((_, cheapest))) = (outcome.path, lowest.path) else {
+            continue;
+        };
+        let route = &outcome.route;
+        assert_eq!(dist, route.len());
+        assert_eq!((route[0], route[dist - 1]), (map.start, map.goal));
+        let mut paid = map.map[map.index(map.start)].0.cost();
+        for pair in route.windows(2) {
+            assert!(map.moves(pair[0]).contains(&pair[1]), "{pair:?}");
+            paid += map.step_cost(pair[0], pair[1]);
+        }
+        assert_eq!(paid, cost, "seed {seed}");
+        assert!(cost >= cheapest, "seed {seed}");
+    }
+}
+
+#[test]
+fn gives_up_once_it_sees_the_goal_is_cut_off() {
+    let map = Map::from_text("6 3\n0 1\n5 1\nffWfff\nffWfff\nffWfff\n").unwrap();
+    let outcome = Explorer::new(1, 1).run(&map, &mut Output::silent());
+    assert!(outcome.path.is_none());
+    // it only had to look at the wall to know
+    assert!(outcome.pops <= 6);
+}
+
+#[test]
+fn frames_only_show_what_has_been_seen() {
+    let map = Map::from_file_path("data/map.txt").unwrap();
+    let mut out = Output::silent();
+    out.snapshots = Some(vec![]);
+    let outcome = Explorer::new(2, 1).run(&map, &mut out);
+    let snapshots = out.snapshots.unwrap();
+    assert_eq!(snapshots.len(), outcome.route.len());
+
+    let first = &snapshots[0];
+    let (x, y) = map.start;
+    for (i, spot) in first.map.iter().enumerate() {
+        let (sx, sy) = (i % map.dim.0, i / map.dim.0);
+        let near = sx.abs_diff(x) <= 2 && sy.abs_diff(y) <= 2;
+        match near {
+            true => assert_eq!(spot.0, map.map[i].0),
+            false => assert_eq!(spot.0.symbol(), '?', "{sx}, {sy}"),
+        }
+    }
+    assert!(first.map_text().contains('?'));
+    // the last frame has the whole walk on it
+    let last = snapshots.last().unwrap();
+    for &loc in &outcome.route {
+        assert_ne!(last.map[last.index(loc)].0.symbol(), '?');
+    }
+}
@@ -0,0 +1,43 @@
+use proj1::{
+    generator::Generator,
+    map::Map,
+    search::{Algorithm, Strategy},
+    Output,
+};
+
+#[test]
+fn same_seed_same_map() {
+    let a = Generator::new(40, 25, 3).generate().file_text();
+    let b = Generator::new(40, 25, 3).generate().file_text();
+    let c = Generator::new(40, 25, 4).generate().file_text();
+    assert_eq!(a, b);
+    assert_ne!(a, c);
+}
+
+#[test]
+fn generated_maps_read_back_in() {
+    let map = Generator::new(33, 17, 11).generate();
+    let path = std::env::temp_dir().join("proj1-generated.txt");
+    std::fs::write(&path, map.file_text()).unwrap();
+    let read = Map::from_file_path(path.to_str().unwrap()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(read.dim, (33, 17));
+    assert_eq!((read.start, read.goal), (map.start, map.goal));
+    assert_eq!(read.file_text(), map.file_text());
+}
+
+#[test]
+fn connected_maps_have_a_path() {
+    for seed in 0..20 {
+        let size = (5 + seed as usize * 3, 4 + seed as usize * 2);
+        let mut generator = Generator::new(size.0, size.1, seed);
+        generator.connected = true;
+        let map = generator.generate();
+        assert_eq!(map.dim, size);
+        if map.start == map.goal {
+            continue;
+        }
+        let lowest = Strategy::lowest_cost_path().run(&map, &mut Output::silent());
+        assert!(lowest.path.is_some(), "seed {seed}");
+    }
+}
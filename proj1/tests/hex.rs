@@ -0,0 +1,69 @@
+use proj1::{
+    map::{dist, DistMode, Map, Status, Terrain},
+    search::{Algorithm, Strategy},
+    Output,
+};
+use std::sync::Arc;
+
+fn open_hex(dim: (usize, usize)) -> Map {
+    let rows = vec![vec![Terrain::ROAD; dim.0]; dim.1];
+    let mut map = Map::new(rows, (0, 0), (dim.0 - 1, dim.1 - 1), Arc::default());
+    map.hex = true;
+    map
+}
+
+#[test]
+fn odd_rows_are_shifted_right() {
+    let map = open_hex((5, 5));
+    let mut even = map.moves((2, 2));
+    even.sort();
+    assert_eq!(even, [(1, 1), (1, 2), (1, 3), (2, 1), (2, 3), (3, 2)]);
+    let mut odd = map.moves((2, 3));
+    odd.sort();
+    assert_eq!(odd, [(1, 3), (2, 2), (2, 4), (3, 2), (3, 3), (3, 4)]);
+    // the edges of the map cut some off
+    assert_eq!(map.moves((0, 0)).len(), 2);
+    assert_eq!(map.moves((0, 1)).len(), 5);
+}
+
+#[test]
+fn back_pointers_lead_back() {
+    let mut map = open_hex((5, 5));
+    for from in [(2, 2), (2, 3)] {
+        for to in map.moves(from) {
+            let status = map.toward(to, from, false);
+            assert!(!matches!(status, Status::Up(_) | Status::Down(_)));
+            map.at_mut(to).unwrap().1 = status;
+            assert_eq!(map.follow(to), Some(from), "{from:?} to {to:?}");
+        }
+    }
+}
+
+#[test]
+fn hex_dist_counts_steps() {
+    // on all road every step costs 1, so breadth first finds the fewest steps
+    let map = open_hex((9, 8));
+    for goal in [(8, 7), (0, 7), (8, 0), (4, 3), (1, 6), (0, 0)] {
+        let mut map = map.clone();
+        map.goal = goal;
+        let outcome = Strategy::breadth_first().run(&map, &mut Output::silent());
+        let steps = outcome.path.unwrap().0 - 1;
+        assert_eq!(dist(map.start, goal, DistMode::Hex), steps, "{goal:?}");
+        assert_eq!(dist(goal, map.start, DistMode::Hex), steps, "{goal:?}");
+    }
+}
+
+#[test]
+fn hex_maps_load_and_draw() {
+    let map = Map::from_file_path("data/map-hex.txt").unwrap();
+    assert!(map.hex && map.diagonals.is_none());
+    assert!(Map::from_text(&map.file_text()).unwrap().hex);
+
+    let text = map.map_text();
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), 2 * map.dim.1 + 1);
+    assert!(lines[0].starts_with(" ╱ ╲"));
+    assert!(lines[1].starts_with("│R S│R  │f  │"));
+    assert!(lines[3].starts_with("  │f  │R  │f  │W  │"));
+    assert!(lines.last().unwrap().starts_with("   ╲ ╱"));
+}
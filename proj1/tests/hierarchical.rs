@@ -0,0 +1,90 @@
+use proj1::{
+    generator::Generator,
+    hierarchical::Hierarchical,
+    map::{Map, Vec2},
+    search::{Algorithm, Strategy},
+    Output,
+};
+
+// Check a route only makes allowed moves, and add up what it cost
+fn route_cost(map: &Map, route: &[Vec2]) -> usize {
+    assert_eq!((route[0], *route.last().unwrap()), (map.start, map.goal));
+    for pair in route.windows(2) {
+        assert!(map.moves(pair[0]).contains(&pair[1]), "{pair:?}");
+    }
+    map.map[map.index(route[0])].0.cost()
+        + route
+            .windows(2)
+            .map(|pair| map.step_cost(pair[0], pair[1]))
+            .sum::<usize>()
+}
+
+#[test]
+fn one_cluster_matches_lowest_cost() {
+    for file in [
+        "data/map.txt",
+        "data/map-small-2.txt",
+        "data/map-hex.txt",
+        "data/map-hills.txt",
+    ] {
+        let map = Map::from_file_path(file).unwrap();
+        let lowest = Strategy::lowest_cost_path().run(&map, &mut Output::silent());
+        let hpa = Hierarchical::new(&map, 100);
+        assert_eq!(hpa.graph.entrances(), 0, "{file}");
+        let outcome = hpa.run(&map, &mut Output::silent());
+        assert_eq!(
+            outcome.path.map(|(_, cost)| cost),
+            lowest.path.map(|(_, cost)| cost),
+            "{file}"
+        );
+    }
+}
+
+#[test]
+fn paths_are_real_and_close_to_the_cheapest() {
+    let (mut total, mut cheapest_total) = (0, 0);
+    for seed in 0..12 {
+        let mut map = Generator::new(48, 36, seed).generate();
+        if seed % 3 == 1 {
+            map.diagonals = Some(1.5);
+        }
+        let lowest = Strategy::lowest_cost_path().run(&map, &mut Output::silent());
+        let outcome =
+            Hierarchical::new(&map, 8 + seed as usize % 3 * 4).run(&map, &mut Output::silent());
+        assert_eq!(outcome.path.is_some(), lowest.path.is_some(), "seed {seed}");
+        let (Some((dist, cost)), Some((_, cheapest))) = (outcome.path, lowest.path) else {
+            continue;
+        };
+        assert_eq!(dist, outcome.route.len());
+        assert_eq!(route_cost(&map, &outcome.route), cost, "seed {seed}");
+        assert!(cost >= cheapest, "seed {seed}");
+        (total, cheapest_total) = (total + cost, cheapest_total + cheapest);
+    }
+    // short paths can come out a lot worse, but not many of them
+    assert!(
+        total * 10 <= cheapest_total * 11,
+        "{total} vs {cheapest_total}"
+    );
+}
+
+#[test]
+fn graph_is_reused_across_queries() {
+    let map = Generator::new(60, 40, 7).generate();
+    let hpa = Hierarchical::new(&map, 10);
+    assert!(hpa.graph.entrances() > 0);
+    let spots: Vec<Vec2> = (0..map.dim.0 * map.dim.1)
+        .map(|i| (i % map.dim.0, i / map.dim.0))
+        .filter(|&loc| map.at(loc).is_some())
+        .step_by(97)
+        .collect();
+    for pair in spots.windows(2) {
+        let mut query = map.clone();
+        (query.start, query.goal) = (pair[0], pair[1]);
+        let lowest = Strategy::lowest_cost_path().run(&query, &mut Output::silent());
+        let outcome = hpa.run(&query, &mut Output::silent());
+        assert_eq!(outcome.path.is_some(), lowest.path.is_some(), "{pair:?}");
+        if outcome.path.is_some() {
+            route_cost(&query, &outcome.route);
+        }
+    }
+}
@@ -0,0 +1,24 @@
+use proj1::{
+    jump_point::JumpPoint,
+    map::{DistMode, Map},
+    search::{Algorithm, Strategy},
+    Output,
+};
+
+const MAPS: [&str; 4] = [
+    "data/map.txt",
+    "data/map-small-1.txt",
+    "data/map-small-2.txt",
+    "data/map-small-3.txt",
+];
+
+#[test]
+fn jump_point_cost_matches_lowest_cost() {
+    for path in MAPS {
+        let map = Map::from_file_path(path).unwrap();
+        let lowest = Strategy::lowest_cost_path().run(&map, &mut Output::silent());
+        let jump = JumpPoint::new(DistMode::TaxiCab).run(&map, &mut Output::silent());
+        let (lowest, jump) = (lowest.path.unwrap(), jump.path.unwrap());
+        assert_eq!(jump.1, lowest.1, "path costs differ on {path}");
+    }
+}
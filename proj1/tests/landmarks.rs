@@ -0,0 +1,63 @@
+use proj1::{
+    landmarks::Landmarks,
+    map::{Map, Terrain},
+    search::{Algorithm, Strategy},
+    Output,
+};
+
+#[test]
+fn landmark_bounds_are_admissible() {
+    let mut map = Map::from_file_path("data/map.txt").unwrap();
+    let landmarks = Landmarks::build(&map, 6);
+    assert_eq!(landmarks.spots.len(), 6);
+    let goal = map.goal;
+    for y in 0..map.dim.1 {
+        for x in 0..map.dim.0 {
+            if map.at((x, y)).is_none() || (x, y) == goal {
+                continue;
+            }
+            map.start = (x, y);
+            let lowest = Strategy::lowest_cost_path().run(&map, &mut Output::silent());
+            let (_, cost) = lowest.path.unwrap();
+            let rest = cost - map.at((x, y)).unwrap().0.cost();
+            assert!(landmarks.estimate((x, y), goal) <= rest, "at {x},{y}");
+        }
+    }
+}
+
+#[test]
+fn landmarks_cut_down_the_search() {
+    let map = Map::from_file_path("data/map.txt").unwrap();
+    let taxicab = Strategy::a_star_taxicab().run(&map, &mut Output::silent());
+    let alt =
+        Strategy::a_star_landmarks(Landmarks::build(&map, 8)).run(&map, &mut Output::silent());
+    assert!(alt.path.is_some());
+    assert!(alt.pops < taxicab.pops);
+}
+
+#[test]
+fn saved_tables_are_reused_only_for_the_same_map() {
+    let mut map = Map::from_file_path("data/map-small-3.txt").unwrap();
+    let path = std::env::temp_dir().join("proj1-map-small-3.landmarks");
+    let path = path.to_str().unwrap();
+    let built = Landmarks::build(&map, 3);
+    built.save(path).unwrap();
+
+    let loaded = Landmarks::load(path, &map).unwrap();
+    assert_eq!(loaded.spots, built.spots);
+    for y in 0..map.dim.1 {
+        for x in 0..map.dim.0 {
+            let loc = (x, y);
+            assert_eq!(
+                loaded.estimate(loc, map.goal),
+                built.estimate(loc, map.goal)
+            );
+        }
+    }
+
+    map.at_mut((4, 4)).unwrap().0 = Terrain::WATER;
+    assert!(Landmarks::load(path, &map).is_none());
+    map.diagonals = Some(1.5);
+    assert!(Landmarks::load(path, &map).is_none());
+    std::fs::remove_file(path).unwrap();
+}
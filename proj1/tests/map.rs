@@ -0,0 +1,72 @@
+use proj1::map::{Map, MapErrorKind};
+
+#[test]
+fn bad_characters_are_located() {
+    let err = Map::from_text("3 2\n0 0\n2 1\nfff\nfxf\n").unwrap_err();
+    assert_eq!(err.kind, MapErrorKind::UnknownTerrain);
+    assert_eq!(
+        (err.line, err.column, err.character),
+        (Some(5), Some(2), Some('x'))
+    );
+    assert_eq!(
+        err.to_string(),
+        "line 5, column 2: Could not parse map character 'x'"
+    );
+}
+
+#[test]
+fn badly_shaped_maps_are_rejected() {
+    let err = Map::from_text("3 2\n0 0\n2 1\nffff\nfff\n").unwrap_err();
+    assert_eq!(err.kind, MapErrorKind::WrongLength(4, 3));
+    assert_eq!(
+        (err.line, err.column, err.character),
+        (Some(4), Some(4), Some('f'))
+    );
+
+    let err = Map::from_text("3 2\n0 0\n2 1\nfff\nff\n").unwrap_err();
+    assert_eq!(err.kind, MapErrorKind::WrongLength(2, 3));
+    assert_eq!(
+        (err.line, err.column, err.character),
+        (Some(5), Some(3), None)
+    );
+
+    let err = Map::from_text("3 2\n0 0\n2 1\nfff\n").unwrap_err();
+    assert_eq!(err.kind, MapErrorKind::MissingRows(1));
+
+    // sizes that don't match the rows are found out from the rows, without trying to
+    // make room for them first
+    let err = Map::from_text("100000000 100000000\n0 0\n2 1\nfff\nfff\n").unwrap_err();
+    assert_eq!(err.kind, MapErrorKind::WrongLength(3, 100000000));
+    let err = Map::from_text("18446744073709551615 2\n0 0\n2 1\nfff\nfff\n").unwrap_err();
+    assert_eq!(err.line, Some(1));
+    assert_eq!(err.to_string(), "line 1: Dimensions are too large");
+
+    let err = Map::from_text("3 2\n0 0\n2 x\nfff\nfff\n").unwrap_err();
+    assert_eq!(err.line, Some(3));
+    assert_eq!(err.to_string(), "line 3: Couldn't parse goal Y (\"x\")");
+}
+
+#[test]
+fn stops_on_water_are_rejected() {
+    let err = Map::from_text("3 2\n1 0\n2 1\nfWf\nfff\n").unwrap_err();
+    assert_eq!(
+        err.kind,
+        MapErrorKind::Impassable("start".into(), "Water".into())
+    );
+    assert_eq!(
+        (err.line, err.column, err.character),
+        (Some(4), Some(2), Some('W'))
+    );
+
+    let err = Map::from_text("3 2\n0 0\n2 1\nfff\nffW\n").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "line 5, column 3: The goal is on Water, which can't be crossed"
+    );
+
+    let err = Map::from_text("3 2\n0 0\n2 1\nwaypoints 1\n1 1\nfff\nfWf\n");
+    assert_eq!(
+        err.unwrap_err().kind,
+        MapErrorKind::Impassable("waypoint 1".into(), "Water".into())
+    );
+}
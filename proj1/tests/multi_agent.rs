@@ -0,0 +1,82 @@
+use proj1::{
+    map::{Map, Terrain, Vec2},
+    multi_agent::ConflictBased,
+    search::{Algorithm, Strategy},
+    Output,
+};
+
+fn at(path: &[Vec2], t: usize) -> Vec2 {
+    path[t.min(path.len() - 1)]
+}
+
+#[test]
+fn agents_never_collide() {
+    let map = Map::from_file_path("data/map-agents.txt").unwrap();
+    assert_eq!(map.agents.len(), 3);
+    let agents: Vec<(Vec2, Vec2)> = std::iter::once((map.start, map.goal))
+        .chain(map.agents.iter().copied())
+        .collect();
+    let (paths, _, _) = ConflictBased::new().plan(&map, &agents, &mut Output::silent());
+    let paths = paths.unwrap();
+
+    for (path, &(start, goal)) in paths.iter().zip(&agents) {
+        assert_eq!((path[0], *path.last().unwrap()), (start, goal));
+        for pair in path.windows(2) {
+            assert!(pair[0] == pair[1] || map.moves(pair[0]).contains(&pair[1]));
+        }
+    }
+    let end = paths.iter().map(|p| p.len()).max().unwrap();
+    for t in 0..end {
+        for a in 0..paths.len() {
+            for b in a + 1..paths.len() {
+                let (pa, pb) = (&paths[a], &paths[b]);
+                assert_ne!(at(pa, t), at(pb, t), "agents {a} and {b} meet at {t}");
+                assert!(
+                    !(at(pa, t) == at(pb, t + 1) && at(pa, t + 1) == at(pb, t)),
+                    "agents {a} and {b} swap at {t}"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn one_agent_matches_lowest_cost() {
+    for file in [
+        "data/map-small-1.txt",
+        "data/map-small-2.txt",
+        "data/map.txt",
+    ] {
+        let mut map = Map::from_file_path(file).unwrap();
+        map.agents.clear();
+        let lowest = Strategy::lowest_cost_path().run(&map, &mut Output::silent());
+        let cbs = ConflictBased::new().run(&map, &mut Output::silent());
+        assert_eq!(
+            cbs.path.map(|(_, cost)| cost),
+            lowest.path.map(|(_, cost)| cost),
+            "{file}"
+        );
+    }
+}
+
+#[test]
+fn agent_with_unreachable_goal_fails() {
+    let mut map = Map::from_file_path("data/map-small-1.txt").unwrap();
+    map.agents = vec![((4, 2), (2, 1))];
+    let outcome = ConflictBased::new().run(&map, &mut Output::silent());
+    assert!(outcome.path.is_none());
+}
+
+#[test]
+fn walled_off_goal_fails_fast() {
+    let mut map = Map::from_file_path("data/map-agents.txt").unwrap();
+    let goal = (1, 3);
+    for next in map.moves(goal) {
+        map.at_mut(next).unwrap().0 = Terrain::WATER;
+    }
+    map.agents.push(((13, 2), goal));
+    let outcome = ConflictBased::new().run(&map, &mut Output::silent());
+    assert!(outcome.path.is_none());
+    // no more than a look over the map for each agent
+    assert!(outcome.pops <= map.dim.0 * map.dim.1 * (map.agents.len() + 1));
+}
@@ -0,0 +1,224 @@
+use proj1::{
+    algorithms::{self, Settings},
+    map::{Map, Terrain, TerrainTable, Vec2},
+    search::{Algorithm, Outcome, Strategy},
+    Output,
+};
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, VecDeque},
+    sync::Arc,
+};
+
+const TERRAINS: [Terrain; 7] = [
+    Terrain::ROAD,
+    Terrain::FIELD,
+    Terrain::FOREST,
+    Terrain::HILLS,
+    Terrain::RIVER,
+    Terrain::MOUNTAINS,
+    Terrain::WATER,
+];
+
+// A small LCG, so every run checks the same maps
+struct Lcg(u64);
+
+impl Lcg {
+    fn below(&mut self, n: usize) -> usize {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        ((self.0 >> 33) % n as u64) as usize
+    }
+}
+
+// A random map with the start and goal on ground that can be crossed
+fn random_map(seed: u64) -> Map {
+    let mut rng = Lcg(seed);
+    let dim = (2 + rng.below(11), 2 + rng.below(9));
+    let mut rows: Vec<Vec<Terrain>> = (0..dim.1)
+        .map(|_| (0..dim.0).map(|_| TERRAINS[rng.below(7)]).collect())
+        .collect();
+    let mut pick = |rows: &mut Vec<Vec<Terrain>>| {
+        let loc = (rng.below(dim.0), rng.below(dim.1));
+        if !rows[loc.1][loc.0].passable() {
+            rows[loc.1][loc.0] = Terrain::FIELD;
+        }
+        loc
+    };
+    let (start, goal) = (pick(&mut rows), pick(&mut rows));
+    let mut map = Map::new(rows, start, goal, Arc::new(TerrainTable::default()));
+    if seed.is_multiple_of(3) {
+        map.diagonals = Some(1.5);
+    } else if seed % 4 == 1 {
+        map.hex = true;
+    }
+    // going uphill costs more than going down, so costs depend on the direction
+    if seed.is_multiple_of(2) {
+        map.elevation = (0..dim.0 * dim.1).map(|_| rng.below(4) as u8).collect();
+        map.climb = 1 + rng.below(3);
+    }
+    map
+}
+
+// The cheapest cost from the start to the goal (counting the start's terrain, like the
+// searches do) and the fewest spots a path can have, each worked out separately
+fn oracle(map: &Map) -> Option<(usize, usize)> {
+    let index = |loc: Vec2| loc.1 * map.dim.0 + loc.0;
+    let mut costs = vec![usize::MAX; map.dim.0 * map.dim.1];
+    let mut q = BinaryHeap::new();
+    costs[index(map.start)] = map.at(map.start).unwrap().0.cost();
+    q.push(Reverse((costs[index(map.start)], map.start)));
+    while let Some(Reverse((cost, loc))) = q.pop() {
+        if cost > costs[index(loc)] {
+            continue;
+        }
+        for next in map.moves(loc) {
+            let new = cost + map.step_cost(loc, next);
+            if new < costs[index(next)] {
+                costs[index(next)] = new;
+                q.push(Reverse((new, next)));
+            }
+        }
+    }
+
+    let mut spots = vec![usize::MAX; map.dim.0 * map.dim.1];
+    let mut q = VecDeque::from([map.start]);
+    spots[index(map.start)] = 1;
+    while let Some(loc) = q.pop_front() {
+        for next in map.moves(loc) {
+            if spots[index(next)] == usize::MAX {
+                spots[index(next)] = spots[index(loc)] + 1;
+                q.push_back(next);
+            }
+        }
+    }
+
+    let goal = index(map.goal);
+    (costs[goal] != usize::MAX).then_some((costs[goal], spots[goal]))
+}
+
+// The route goes from the start to the goal one legal move at a time, never onto water,
+// and adds up to the length and cost that were reported
+fn check_route(map: &Map, name: &str, outcome: &Outcome) {
+    let (dist, cost) = outcome.path.unwrap();
+    let route = &outcome.route;
+    assert_eq!(route.first(), Some(&map.start), "{name}");
+    assert_eq!(route.last(), Some(&map.goal), "{name}");
+    assert_eq!(route.len(), dist, "{name}");
+    assert!(route.iter().all(|&loc| map.at(loc).is_some()), "{name}");
+    let mut total = map.at(map.start).unwrap().0.cost();
+    for pair in route.windows(2) {
+        assert!(map.moves(pair[0]).contains(&pair[1]), "{name}: {pair:?}");
+        total += map.step_cost(pair[0], pair[1]);
+    }
+    assert_eq!(total, cost, "{name}");
+}
+
+#[test]
+fn cost_searches_find_the_cheapest_path() {
+    let mut checked = 0;
+    for seed in 0..300 {
+        let map = random_map(seed);
+        let Some((cheapest, _)) = oracle(&map) else {
+            continue;
+        };
+        let mut searches = vec![Strategy::lowest_cost_path()];
+        // with diagonal moves a road diagonal costs less than its length, and on hex
+        // maps a step can change both x and y, so the distance heuristics overestimate
+        if map.hex {
+            searches.push(Strategy::a_star_hex());
+        } else if map.diagonals.is_none() {
+            searches.push(Strategy::a_star_taxicab());
+            searches.push(Strategy::a_star_euclidean());
+        }
+        for search in searches {
+            let outcome = search.run(&map, &mut Output::silent());
+            check_route(&map, search.name(), &outcome);
+            assert_eq!(
+                outcome.path.unwrap().1,
+                cheapest,
+                "seed {seed}: {}",
+                search.name()
+            );
+        }
+        checked += 1;
+    }
+    assert!(checked > 100);
+}
+
+#[test]
+fn breadth_first_takes_the_fewest_steps() {
+    for seed in 0..300 {
+        let map = random_map(seed);
+        let outcome = Strategy::breadth_first().run(&map, &mut Output::silent());
+        match oracle(&map) {
+            Some((_, fewest)) => {
+                check_route(&map, "breadth_first", &outcome);
+                assert_eq!(outcome.path.unwrap().0, fewest, "seed {seed}");
+            }
+            None => assert!(outcome.path.is_none(), "seed {seed}"),
+        }
+    }
+}
+
+// The searches that don't promise the cheapest path: they count steps instead of cost,
+// inflate their heuristic, plan over a coarser graph or can't see the whole map
+const NOT_CHEAPEST: &[&str] = &[
+    "breadth_first",
+    "greedy_best_first",
+    "iddfs",
+    "bidirectional_bfs",
+    "weighted_a_star",
+    "hpa_star",
+    "lrta_star",
+];
+
+#[test]
+fn every_path_found_is_legal() {
+    for seed in 0..60 {
+        let map = random_map(seed);
+        let cheapest = oracle(&map).map(|(cost, _)| cost);
+        for alg in algorithms::all(&map, &Settings::default()) {
+            let outcome = alg.run(&map, &mut Output::silent());
+            assert_eq!(
+                outcome.path.is_some(),
+                cheapest.is_some(),
+                "seed {seed}: {}",
+                alg.name()
+            );
+            let Some(cheapest) = cheapest else {
+                continue;
+            };
+            check_route(&map, alg.name(), &outcome);
+            if !NOT_CHEAPEST.contains(&alg.name()) {
+                assert_eq!(
+                    outcome.path.unwrap().1,
+                    cheapest,
+                    "seed {seed}: {}",
+                    alg.name()
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn unreachable_goals_fail_everywhere() {
+    for seed in 0..30 {
+        let mut map = random_map(seed);
+        // wall the goal in (diagonals can't cut the corners of water)
+        for next in map.moves(map.goal) {
+            map.at_mut(next).unwrap().0 = Terrain::WATER;
+        }
+        if map.start == map.goal || map.at(map.start).is_none() {
+            continue;
+        }
+        for alg in algorithms::all(&map, &Settings::default()) {
+            let outcome = alg.run(&map, &mut Output::silent());
+            assert!(outcome.path.is_none(), "seed {seed}: {}", alg.name());
+            assert!(outcome.route.is_empty(), "seed {seed}: {}", alg.name());
+        }
+    }
+}
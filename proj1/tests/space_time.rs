@@ -0,0 +1,175 @@
+use proj1::{
+    generator::Generator,
+    map::{Hazard, Map, MapErrorKind, Vec2},
+    search::{Algorithm, Counts, Strategy},
+    space_time::SpaceTime,
+    Output,
+};
+use std::collections::BTreeSet;
+
+// Check a timed route keeps clear of every hazard, and add up what it cost
+fn check(map: &Map, route: &[Vec2]) -> usize {
+    assert_eq!((route[0], *route.last().unwrap()), (map.start, map.goal));
+    let mut cost = map.terrain_at(route[0], 0).cost();
+    for (t, pair) in route.windows(2).enumerate() {
+        let (from, to) = (pair[0], pair[1]);
+        assert!(from == to || map.moves_at(from, t + 1).contains(&to), "{t}");
+        assert!(map.terrain_at(to, t + 1).passable(), "flooded at {t}");
+        for hazard in &map.hazards {
+            assert_ne!(hazard.patrol_at(t + 1), Some(to), "caught at {}", t + 1);
+            assert!(
+                from == to
+                    || hazard.patrol_at(t) != Some(to)
+                    || hazard.patrol_at(t + 1) != Some(from),
+                "passed a patrol at {t}"
+            );
+        }
+        cost += map.step_cost_at(from, to, t);
+    }
+    cost
+}
+
+// The cheapest cost to the goal by brute force, a step at a time up to `horizon`
+fn cheapest(map: &Map, horizon: usize) -> Option<usize> {
+    let patrol_on = |loc: Vec2, t: usize| map.hazards.iter().any(|h| h.patrol_at(t) == Some(loc));
+    let mut best = vec![usize::MAX; map.dim.0 * map.dim.1];
+    if !map.terrain_at(map.start, 0).passable() || patrol_on(map.start, 0) {
+        return None;
+    }
+    best[map.index(map.start)] = map.terrain_at(map.start, 0).cost();
+    let mut found = usize::MAX;
+    for t in 0..horizon {
+        found = found.min(best[map.index(map.goal)]);
+        let mut next = vec![usize::MAX; best.len()];
+        let spots: BTreeSet<Vec2> = (0..best.len())
+            .filter(|&i| best[i] != usize::MAX)
+            .map(|i| (i % map.dim.0, i / map.dim.0))
+            .collect();
+        for &loc in &spots {
+            let mut tos = map.moves_at(loc, t + 1);
+            if map.terrain_at(loc, t + 1).passable() {
+                tos.push(loc);
+            }
+            for to in tos {
+                let swap = map.hazards.iter().any(|h| {
+                    loc != to && h.patrol_at(t) == Some(to) && h.patrol_at(t + 1) == Some(loc)
+                });
+                if patrol_on(to, t + 1) || swap {
+                    continue;
+                }
+                let cost = best[map.index(loc)] + map.step_cost_at(loc, to, t);
+                let i = map.index(to);
+                next[i] = next[i].min(cost);
+            }
+        }
+        best = next;
+    }
+    (found != usize::MAX).then_some(found)
+}
+
+#[test]
+fn hazards_are_parsed_and_saved() {
+    let map = Map::from_file_path("data/map-hazards.txt").unwrap();
+    assert_eq!(map.hazards.len(), 2);
+    assert!(matches!(
+        &map.hazards[0],
+        Hazard::Tide {
+            from: 4,
+            to: 7,
+            every: Some(8),
+            ..
+        }
+    ));
+    assert!(map.terrain_at((6, 3), 3).passable());
+    assert!(!map.terrain_at((6, 3), 4).passable());
+    assert!(map.terrain_at((6, 3), 8).passable());
+    assert_eq!(map.hazards[1].patrol_at(9), Some((9, 2)));
+    assert_eq!(
+        Map::from_text(&map.file_text()).unwrap().hazards,
+        map.hazards
+    );
+
+    for (name, line) in [
+        ("bad-spot", "patrol 1,1 2"),
+        ("bad-kind", "flood f steps 1-2: 1,1"),
+        ("bad-steps", "tide W steps 3-1: 1,1"),
+        ("bad-cycle", "tide W steps 1-4 every 4: 1,1"),
+        ("out-of-bounds", "patrol 1,1 3,1"),
+    ] {
+        let err =
+            Map::from_text(&format!("3 2\n0 0\n2 1\nhazards 1\n{line}\nfff\nfff\n")).unwrap_err();
+        assert!(matches!(err.kind, MapErrorKind::Header(_)), "{name}");
+        assert_eq!(err.line, Some(5), "{name}");
+    }
+}
+
+#[test]
+fn no_hazards_matches_lowest_cost() {
+    for file in [
+        "data/map-small-1.txt",
+        "data/map-small-2.txt",
+        "data/map.txt",
+    ] {
+        let map = Map::from_file_path(file).unwrap();
+        let lowest = Strategy::lowest_cost_path().run(&map, &mut Output::silent());
+        let timed = SpaceTime::new().run(&map, &mut Output::silent());
+        assert_eq!(
+            timed.path.map(|(_, cost)| cost),
+            lowest.path.map(|(_, cost)| cost),
+            "{file}"
+        );
+    }
+}
+
+#[test]
+fn waits_for_the_tide_and_dodges_the_patrol() {
+    let map = Map::from_file_path("data/map-hazards.txt").unwrap();
+    let outcome = SpaceTime::new().run(&map, &mut Output::silent());
+    let cost = check(&map, &outcome.route);
+    assert_eq!(outcome.path.unwrap().1, cost);
+    assert!(outcome.route.windows(2).any(|pair| pair[0] == pair[1]));
+
+    // there's no getting past a guard that paces all of a one-wide causeway
+    let map =
+        Map::from_text("5 3\n0 1\n4 1\nhazards 1\npatrol 3,1 2,1 1,1 2,1\nWWWWW\nRRRRR\nWWWWW\n")
+            .unwrap();
+    assert!(Strategy::lowest_cost_path()
+        .run(&map, &mut Output::silent())
+        .path
+        .is_some());
+    assert!(SpaceTime::new()
+        .plan(&map, &mut Counts::default(), &mut Output::silent())
+        .is_none());
+}
+
+#[test]
+fn matches_brute_force_on_random_maps() {
+    for seed in 0..20 {
+        let mut map = Generator::new(7, 6, seed).generate();
+        let spots = |offset: usize| {
+            (0..4)
+                .map(|i| ((seed as usize + i * 3 + offset) % 7, (i + offset) % 6))
+                .collect::<Vec<Vec2>>()
+        };
+        map.hazards = vec![
+            Hazard::Patrol(spots(1)),
+            Hazard::Tide {
+                terrain: map.terrains.parse("W").unwrap(),
+                from: seed as usize % 3,
+                to: seed as usize % 3 + 1,
+                every: (seed % 2 == 0).then_some(5),
+                spots: spots(2),
+            },
+        ];
+        let mut counts = Counts::default();
+        let plan = SpaceTime::new().plan(&map, &mut counts, &mut Output::silent());
+        if let Some((route, cost)) = &plan {
+            assert_eq!(check(&map, route), *cost, "seed {seed}");
+        }
+        assert_eq!(
+            plan.map(|(_, cost)| cost),
+            cheapest(&map, 200),
+            "seed {seed}"
+        );
+    }
+}
@@ -0,0 +1,80 @@
+use proj1::{
+    map::{Map, Status},
+    search::{Algorithm, Strategy},
+    stepper::{Key, Stepper},
+    Output,
+};
+
+fn frames(strategy: Strategy) -> Vec<Map> {
+    let map = Map::from_file_path("data/map-small-1.txt").unwrap();
+    let mut out = Output::silent();
+    out.snapshots = Some(vec![]);
+    strategy.run(&map, &mut out);
+    out.snapshots.unwrap()
+}
+
+fn stepper(strategy: Strategy) -> Stepper {
+    Stepper::new(frames(strategy))
+}
+
+#[test]
+fn keys_and_arrows_parse() {
+    assert_eq!(
+        Key::parse(b"n\x1b[Dp \x1b[C+-gcxq"),
+        [
+            Key::Next,
+            Key::Back,
+            Key::Back,
+            Key::Play,
+            Key::Next,
+            Key::Faster,
+            Key::Slower,
+            Key::Goal,
+            Key::Costs,
+            Key::Quit
+        ]
+    );
+}
+
+#[test]
+fn stepping_stays_in_bounds() {
+    let mut s = stepper(Strategy::breadth_first());
+    let last = s.len() - 1;
+    s.press(Key::Back);
+    assert_eq!(s.at, 0);
+    s.press(Key::Next);
+    s.press(Key::Next);
+    assert_eq!(s.at, 2);
+    s.press(Key::Last);
+    s.press(Key::Next);
+    assert_eq!(s.at, last);
+
+    // playing from the end starts over, and stops at the end again
+    s.press(Key::Play);
+    assert!(s.playing && s.at == 0);
+    for _ in 0..=last {
+        s.tick();
+    }
+    assert!(!s.playing && s.at == last);
+}
+
+#[test]
+fn goal_frame_and_costs() {
+    let frames = frames(Strategy::lowest_cost_path());
+    let mut s = Stepper::new(frames.clone());
+    let goal = s.goal_frame().unwrap();
+    assert!(goal > 0 && goal < s.len() - 1);
+    // the goal is settled in that frame, and was still on the frontier the frame before
+    let status = |frame: &Map| frame.at(frame.goal).unwrap().1;
+    assert!(!status(&frames[goal]).is_active());
+    assert!(status(&frames[goal]) != Status::Untraversed);
+    assert!(status(&frames[goal - 1]).is_active());
+    s.press(Key::Goal);
+    assert_eq!(s.at, goal);
+    assert!(s.screen("lowest_cost").contains("costs on"));
+    s.press(Key::Costs);
+    let screen = s.screen("lowest_cost");
+    assert!(screen.starts_with("lowest_cost\n"));
+    assert!(screen.contains(&format!("frame {goal}/{}", s.len() - 1)));
+    assert!(screen.contains("costs off"));
+}
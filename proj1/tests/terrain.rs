@@ -0,0 +1,49 @@
+use proj1::{
+    map::{Map, Terrain, TerrainTable},
+    search::{Algorithm, Strategy},
+    Output,
+};
+use std::sync::Arc;
+
+#[test]
+fn standard_terrain_file_matches_default() {
+    let table = TerrainTable::from_file_path("data/terrain.txt").unwrap();
+    assert_eq!(table, TerrainTable::default());
+    assert_eq!(table.get('M'), Some(Terrain::MOUNTAINS));
+    assert_eq!(table.parse("water"), Some(Terrain::WATER));
+    assert_eq!(table.name(Terrain::FOREST), "Forest");
+}
+
+#[test]
+fn maps_use_the_loaded_terrain() {
+    let amphibious = Arc::new(TerrainTable::from_file_path("data/terrain-amphibious.txt").unwrap());
+    let err = Map::from_file_path("data/map-marsh.txt").err().unwrap();
+    assert_eq!(
+        err.to_string(),
+        "line 4, column 1: Could not parse map character 'm'"
+    );
+
+    let map = Map::from_file_path_with("data/map-marsh.txt", amphibious.clone()).unwrap();
+    assert!(map.at((3, 0)).is_some(), "water should be passable");
+    let text = map.map_text();
+    assert!(text.contains("▐m  ┃m  ┃m  ┃W  ┃W  ┃f  ┃f  ┃f  ▌"));
+    assert!(text.contains("┃=  ┃"));
+
+    // swimming across the lake beats walking around it
+    let map = Map::from_file_path_with("data/map-small-1.txt", amphibious).unwrap();
+    let lowest = Strategy::lowest_cost_path().run(&map, &mut Output::silent());
+    assert_eq!(lowest.path.unwrap().1, 23);
+}
+
+#[test]
+fn one_letter_names_are_not_mistaken_for_symbols() {
+    let path = std::env::temp_dir().join("proj1-one-letter.terrain");
+    let path = path.to_str().unwrap();
+    std::fs::write(path, "R Road 1\nx R 3\n").unwrap();
+    let table = TerrainTable::from_file_path(path).unwrap();
+    assert_eq!(table.get('x').map(|t| t.cost()), Some(3));
+
+    std::fs::write(path, "R Road 1\nx r 3\ny R 4\n").unwrap();
+    let err = TerrainTable::from_file_path(path).unwrap_err();
+    assert_eq!(err, "Terrain line 3: 'y' or \"R\" is already defined");
+}
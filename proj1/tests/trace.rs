@@ -0,0 +1,123 @@
+mod common;
+
+use common::Shared;
+use proj1::{
+    algorithms::{self, Settings},
+    map::Map,
+    search::{Algorithm, Strategy},
+    trace::Event,
+    Output,
+};
+
+fn traced(map: &Map, alg: &dyn Algorithm) -> (proj1::search::Outcome, Vec<String>) {
+    let sink = Shared::default();
+    let mut out = Output::silent();
+    out.trace = Some(Box::new(sink.clone()));
+    let outcome = alg.run(map, &mut out);
+    let text = sink.text();
+    (outcome, text.lines().map(String::from).collect())
+}
+
+fn field<'a>(line: &'a str, name: &str) -> &'a str {
+    let rest = &line[line.find(&format!("\"{name}\": ")).unwrap() + name.len() + 4..];
+    let end = match rest.starts_with('[') {
+        true => rest.find(']').unwrap() + 1,
+        false => rest.find([',', '}']).unwrap(),
+    };
+    &rest[..end]
+}
+
+#[test]
+fn every_pop_was_pushed_and_the_backtrack_is_the_path() {
+    let map = Map::from_file_path("data/map-small-2.txt").unwrap();
+    let (outcome, lines) = traced(&map, &Strategy::a_star_taxicab());
+    let mut pushed = vec![];
+    let mut backtracked = vec![];
+    for line in &lines {
+        assert!(
+            line.starts_with("{\"event\": \"") && line.ends_with('}'),
+            "{line}"
+        );
+        match field(line, "event") {
+            "\"push\"" => pushed.push(field(line, "loc").to_string()),
+            "\"pop\"" => assert!(pushed.contains(&field(line, "loc").to_string())),
+            "\"backtrack\"" => backtracked.push(field(line, "loc").to_string()),
+            _ => (),
+        }
+    }
+    let pops = lines.iter().filter(|l| l.contains("\"pop\"")).count();
+    assert!(pops > 0 && pops <= outcome.pops);
+    let route: Vec<String> = outcome
+        .route
+        .iter()
+        .rev()
+        .map(|&(x, y)| format!("[{x}, {y}]"))
+        .collect();
+    assert_eq!(backtracked, route);
+    let (dist, cost) = outcome.path.unwrap();
+    assert_eq!(lines.last().unwrap(), &Event::Path { dist, cost }.json());
+    let goal = lines.iter().find(|l| l.contains("\"goal\"")).unwrap();
+    assert_eq!(field(goal, "g"), cost.to_string());
+}
+
+#[test]
+fn failures_end_with_a_fail_event() {
+    let mut map = Map::from_file_path("data/map-small-1.txt").unwrap();
+    map.goal = (2, 1); // in the water
+    let (outcome, lines) = traced(&map, &Strategy::breadth_first());
+    assert!(outcome.path.is_none());
+    assert_eq!(lines.last().unwrap(), "{\"event\": \"fail\"}");
+    assert!(!lines.iter().any(|l| l.contains("\"goal\"")));
+}
+
+#[test]
+fn other_searches_trace_their_frontier_too() {
+    let map = Map::from_file_path("data/map-small-2.txt").unwrap();
+    let settings = Settings::default();
+    for name in [
+        "iddfs",
+        "ida_star_1",
+        "d_star_lite",
+        "waypoints",
+        "cbs",
+        "space_time_a_star",
+        "hpa_star",
+    ] {
+        let alg = algorithms::from_name(name, &map, &settings).unwrap();
+        let (outcome, lines) = traced(&map, alg.as_ref());
+        let mut pushed = vec![];
+        let mut pops = 0;
+        for line in &lines {
+            match field(line, "event") {
+                "\"push\"" => pushed.push(field(line, "loc").to_string()),
+                "\"pop\"" => {
+                    assert!(pushed.contains(&field(line, "loc").to_string()), "{name}");
+                    pops += 1;
+                }
+                _ => (),
+            }
+        }
+        assert!(pops > 0 && pops <= outcome.pops, "{name}");
+        assert!(lines.iter().any(|l| l.contains("\"goal\"")), "{name}");
+        let (dist, cost) = outcome.path.unwrap();
+        assert_eq!(
+            lines.last().unwrap(),
+            &Event::Path { dist, cost }.json(),
+            "{name}"
+        );
+        assert_eq!(
+            lines.iter().filter(|l| l.contains("\"path\"")).count(),
+            1,
+            "{name}"
+        );
+    }
+
+    let mut map = Map::from_file_path("data/map-small-1.txt").unwrap();
+    map.goal = (2, 1); // in the water
+    for name in ["waypoints", "cbs"] {
+        let alg = algorithms::from_name(name, &map, &settings).unwrap();
+        let (outcome, lines) = traced(&map, alg.as_ref());
+        assert!(outcome.path.is_none());
+        assert_eq!(lines.last().unwrap(), "{\"event\": \"fail\"}", "{name}");
+    }
+}
@@ -0,0 +1,112 @@
+use proj1::{
+    map::{Map, Vec2},
+    search::{Algorithm, Strategy},
+    waypoints::WaypointRoute,
+    Output,
+};
+
+// The cost of the best path from `a` to `b`, not counting the spot at `a`
+fn leg(map: &Map, a: Vec2, b: Vec2) -> usize {
+    let mut leg = map.clone();
+    leg.start = a;
+    leg.goal = b;
+    let (_, cost) = Strategy::lowest_cost_path()
+        .run(&leg, &mut Output::silent())
+        .path
+        .unwrap();
+    cost - map.at(a).unwrap().0.cost()
+}
+
+fn permutations(items: Vec<Vec2>) -> Vec<Vec<Vec2>> {
+    if items.is_empty() {
+        return vec![vec![]];
+    }
+    let mut all = vec![];
+    for i in 0..items.len() {
+        let mut rest = items.clone();
+        let first = rest.remove(i);
+        for mut perm in permutations(rest) {
+            perm.insert(0, first);
+            all.push(perm);
+        }
+    }
+    all
+}
+
+#[test]
+fn route_visits_waypoints_in_the_cheapest_order() {
+    let map = Map::from_file_path("data/map-waypoints.txt").unwrap();
+    assert_eq!(map.waypoints.len(), 4);
+    let outcome = WaypointRoute::new().run(&map, &mut Output::silent());
+    let (dist, cost) = outcome.path.unwrap();
+
+    // the route is one contiguous walk through every waypoint
+    let route = outcome.route;
+    assert_eq!(route.len(), dist);
+    assert_eq!((route[0], route[dist - 1]), (map.start, map.goal));
+    assert!(route
+        .windows(2)
+        .all(|pair| map.moves(pair[0]).contains(&pair[1])));
+    assert!(map.waypoints.iter().all(|w| route.contains(w)));
+
+    // and no other order is cheaper
+    let best = permutations(map.waypoints.clone())
+        .into_iter()
+        .map(|order| {
+            let mut stops = vec![map.start];
+            stops.extend(order);
+            stops.push(map.goal);
+            stops
+                .windows(2)
+                .map(|pair| leg(&map, pair[0], pair[1]))
+                .sum::<usize>()
+        })
+        .min()
+        .unwrap();
+    assert_eq!(cost, best + map.at(map.start).unwrap().0.cost());
+
+    // the heuristic finds it too on a map this small
+    let mut heuristic = WaypointRoute::new();
+    heuristic.exact_limit = 0;
+    let outcome = heuristic.run(&map, &mut Output::silent());
+    assert_eq!(outcome.path.unwrap().1, cost);
+}
+
+#[test]
+fn unreachable_waypoint_fails() {
+    let mut map = Map::from_file_path("data/map-waypoints.txt").unwrap();
+    map.waypoints.push((7, 10)); // in the lake
+    let outcome = WaypointRoute::new().run(&map, &mut Output::silent());
+    assert!(outcome.path.is_none());
+}
+
+#[test]
+fn legs_are_searched_both_ways() {
+    // Walking a leg backward climbs where it went down, and pays for the diagonals
+    // onto the other ends, so the cheapest way back can be a different path entirely
+    let mut map = Map::from_text(
+        "6 5\n0 0\n5 4\nwaypoints 2\n3 1\n1 2\n\
+         MMhMRF\nRffMMF\nhFffMF\nFhffff\nfFhfRR\n\
+         elevation\n221232\n121313\n202002\n212133\n220010\n",
+    )
+    .unwrap();
+    map.diagonals = Some(1.5);
+    let (a, b) = (map.waypoints[0], map.waypoints[1]);
+    let cheapest = |stops: [Vec2; 4]| {
+        stops
+            .windows(2)
+            .map(|pair| leg(&map, pair[0], pair[1]))
+            .sum::<usize>()
+            + map.at(map.start).unwrap().0.cost()
+    };
+    let (ab, ba) = (
+        cheapest([map.start, a, b, map.goal]),
+        cheapest([map.start, b, a, map.goal]),
+    );
+    assert!(ba < ab, "{ba} vs {ab}");
+
+    let outcome = WaypointRoute::new().run(&map, &mut Output::silent());
+    assert_eq!(outcome.path.unwrap().1, ba);
+    let visit = |w: Vec2| outcome.route.iter().position(|&loc| loc == w);
+    assert!(visit(b) < visit(a));
+}